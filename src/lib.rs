@@ -0,0 +1,6951 @@
+use elf::ElfBytes;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::fs;
+use std::io::Write;
+use std::time::{Duration, Instant};
+// The kind of a tag, written as a single letter after the ;" of each line
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+enum Kind {
+    Function,
+    // an Ada procedure or function
+    Subprogram,
+    // an Ada or Go package, only used as a scope
+    Package,
+    // a name imported with a C++ using declaration or namespace alias
+    Imported,
+    // a global or namespace scope variable
+    Variable,
+    // a #define, from the macro sections of a -g3 build
+    Macro,
+    // the rest are only used as scopes
+    Namespace,
+    Class,
+    Struct,
+    Union,
+    // a Go method's receiver type
+    Type,
+}
+// Every kind, used to look kinds up by name
+const ALL_KINDS: [Kind; 11] = [
+    Kind::Function,
+    Kind::Subprogram,
+    Kind::Package,
+    Kind::Imported,
+    Kind::Variable,
+    Kind::Macro,
+    Kind::Namespace,
+    Kind::Class,
+    Kind::Struct,
+    Kind::Union,
+    Kind::Type,
+];
+impl Kind {
+    fn from_long_name(name: &str) -> Option<Kind> {
+        ALL_KINDS.iter().copied().find(|k| k.long_name() == name)
+    }
+    fn letter(self) -> char {
+        match self {
+            Kind::Function => 'f',
+            Kind::Subprogram => 'r',
+            Kind::Package => 'p',
+            Kind::Imported => 'N',
+            Kind::Variable => 'v',
+            Kind::Macro => 'd',
+            Kind::Namespace => 'n',
+            Kind::Class => 'c',
+            Kind::Struct => 's',
+            Kind::Union => 'u',
+            Kind::Type => 't',
+        }
+    }
+    fn long_name(self) -> &'static str {
+        match self {
+            Kind::Function => "function",
+            Kind::Subprogram => "subprogram",
+            Kind::Package => "package",
+            Kind::Imported => "name",
+            Kind::Variable => "variable",
+            Kind::Macro => "macro",
+            Kind::Namespace => "namespace",
+            Kind::Class => "class",
+            Kind::Struct => "struct",
+            Kind::Union => "union",
+            Kind::Type => "type",
+        }
+    }
+    // kinds left out unless asked for with --kinds
+    fn enabled_by_default(self) -> bool {
+        !matches!(self, Kind::Imported | Kind::Variable)
+    }
+    fn description(self) -> &'static str {
+        match self {
+            Kind::Function => "functions and methods",
+            Kind::Subprogram => "procedures and functions",
+            Kind::Package => "packages, as a scope",
+            Kind::Imported => "names brought in by using declarations and namespace aliases",
+            Kind::Variable => "global and namespace scope variables",
+            Kind::Macro => "#define macros, only in builds with -g3",
+            Kind::Namespace => "namespaces, as a scope",
+            Kind::Class => "classes, as a scope",
+            Kind::Struct => "structs, as a scope",
+            Kind::Union => "unions, as a scope",
+            Kind::Type => "method receiver types, as a scope",
+        }
+    }
+}
+// The kinds each language's units can give, for --list-kinds. Languages not
+// listed are read like C++.
+const LANGUAGE_KINDS: [(&str, &[Kind]); 4] = [
+    ("C", &[Kind::Function, Kind::Variable, Kind::Macro]),
+    (
+        "C++",
+        &[
+            Kind::Function,
+            Kind::Imported,
+            Kind::Variable,
+            Kind::Macro,
+            Kind::Namespace,
+            Kind::Class,
+            Kind::Struct,
+            Kind::Union,
+        ],
+    ),
+    ("Ada", &[Kind::Subprogram, Kind::Variable, Kind::Package]),
+    (
+        "Go",
+        &[Kind::Function, Kind::Variable, Kind::Package, Kind::Type],
+    ),
+];
+// The --list-kinds output: each language on its own line, followed by a tab
+// indented "letter<tab>name<tab>on|off<tab>description" line per kind, on or
+// off being whether it is written without --kinds. The letters are those
+// written, after --kind-map.
+fn kind_list(kind_map: &BTreeMap<Kind, char>) -> String {
+    let mut list = String::new();
+    for (language, kinds) in LANGUAGE_KINDS {
+        list.push_str(language);
+        list.push('\n');
+        for kind in kinds {
+            list.push_str(&format!(
+                "\t{}\t{}\t{}\t{}\n",
+                kind_map.get(kind).unwrap_or(&kind.letter()),
+                kind.long_name(),
+                if kind.enabled_by_default() {
+                    "on"
+                } else {
+                    "off"
+                },
+                kind.description()
+            ));
+        }
+    }
+    list
+}
+// The languages --kinds can be given for
+const KIND_LANGUAGES: [&str; 6] = ["c", "c++", "ada", "go", "rust", "fortran"];
+// Other names --kinds takes for them, as file extensions spell them
+const KIND_LANGUAGE_ALIASES: [(&str, &str); 2] = [("cpp", "c++"), ("cxx", "c++")];
+// The name --kinds knows a unit's DW_AT_language by
+fn kind_language(language: Option<gimli::DwLang>) -> Option<&'static str> {
+    match language {
+        language if is_c(language) => Some("c"),
+        language if is_ada(language) => Some("ada"),
+        language if is_go(language) => Some("go"),
+        Some(gimli::DW_LANG_C_plus_plus)
+        | Some(gimli::DW_LANG_C_plus_plus_03)
+        | Some(gimli::DW_LANG_C_plus_plus_11)
+        | Some(gimli::DW_LANG_C_plus_plus_14)
+        | Some(gimli::DW_LANG_C_plus_plus_17)
+        | Some(gimli::DW_LANG_C_plus_plus_20) => Some("c++"),
+        Some(gimli::DW_LANG_Rust) => Some("rust"),
+        Some(gimli::DW_LANG_Fortran77)
+        | Some(gimli::DW_LANG_Fortran90)
+        | Some(gimli::DW_LANG_Fortran95)
+        | Some(gimli::DW_LANG_Fortran03)
+        | Some(gimli::DW_LANG_Fortran08)
+        | Some(gimli::DW_LANG_Fortran18) => Some("fortran"),
+        _otherwise => None,
+    }
+}
+// The kinds to tag in a unit of the given language
+fn unit_kinds(language: Option<gimli::DwLang>, options: &Options) -> &[Kind] {
+    let by_language = kind_language(language)
+        .and_then(|name| options.kinds_by_language.iter().find(|(n, _)| *n == name));
+    match by_language {
+        Some((_, kinds)) => kinds,
+        None => &options.kinds,
+    }
+}
+// Allow the list of function info to be sorted
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+struct FunctionInfo {
+    func_name: String,
+    file_name: String,
+    line_number: u64,
+    // 0 when the compiler doesn't record columns, clang never does
+    column_number: u64,
+    kind: Kind,
+    // the kind and name of what the tag is declared in, e.g. an Ada package
+    scope: Option<(Kind, String)>,
+    // extra name:value extension fields
+    fields: Vec<(&'static str, String)>,
+    // the unit's compilation directory, relative file names are relative to it
+    comp_dir: Option<String>,
+}
+
+trait Reader: gimli::Reader<Offset = usize> {}
+impl<'input, Endian> Reader for gimli::EndianSlice<'input, Endian> where Endian: gimli::Endianity {}
+// How a DIE refers to the DIE holding the rest of its attributes
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum OriginKind {
+    // DW_AT_abstract_origin, used by inlined and concrete out of line instances
+    AbstractOrigin,
+    // DW_AT_specification, used by definitions of earlier declarations
+    Specification,
+    // DW_AT_import, the entity a using declaration brings into scope
+    Import,
+}
+// A reference that leaves the unit: a DW_FORM_ref_addr pointing into another
+// unit of .debug_info, or the type signature of a type unit (DW_FORM_ref_sig8)
+#[derive(Clone, Copy)]
+enum OtherUnitRef {
+    Info(gimli::DebugInfoOffset),
+    Signature(gimli::DebugTypeSignature),
+    // into the .debug_info of the supplementary file
+    Sup(gimli::DebugInfoOffset),
+}
+// The attributes of a single DIE that make up a tag
+#[derive(Default)]
+struct DeclAttrs {
+    func_name: Option<String>,
+    // DW_AT_linkage_name, the mangled name
+    linkage_name: Option<String>,
+    file_name: Option<String>,
+    line_number: Option<u64>,
+    column_number: Option<u64>,
+    low_pc: Option<u64>,
+    visibility: Option<gimli::DwVis>,
+    origin: Option<(OriginKind, gimli::UnitOffset)>,
+    // an abstract origin or specification in another unit, only set when
+    // origin isn't
+    other_origin: Option<(OriginKind, OtherUnitRef)>,
+    // DW_AT_type
+    type_offset: Option<gimli::UnitOffset>,
+    // DW_AT_declaration, a declaration that is defined elsewhere
+    declaration: bool,
+    // DW_AT_prototyped, a C function declared with a prototype rather than
+    // K&R style
+    prototyped: bool,
+    // DW_AT_reference or DW_AT_rvalue_reference, the ref-qualifier of a C++
+    // member function
+    ref_qualifier: Option<&'static str>,
+}
+// What is known about the unit whose DIEs are being walked
+struct UnitInfo {
+    language: Option<gimli::DwLang>,
+    comp_dir: Option<String>,
+    // the path of each DW_AT_decl_file index, resolved once per unit
+    files: Vec<Option<String>>,
+}
+fn unit_info<R: Reader>(dwarf: &gimli::Dwarf<R>, unit: &gimli::Unit<R>) -> UnitInfo {
+    let files = match &unit.line_program {
+        Some(line_program) => file_table(dwarf, unit, line_program.header()),
+        None => Vec::new(),
+    };
+    UnitInfo {
+        language: unit_language(unit),
+        comp_dir: unit
+            .comp_dir
+            .clone()
+            .and_then(|d| d.to_string().ok().map(|d| d.to_string())),
+        files,
+    }
+}
+// The path of each file index of a line program
+fn file_table<R: Reader>(
+    dwarf: &gimli::Dwarf<R>,
+    unit: &gimli::Unit<R>,
+    header: &gimli::LineProgramHeader<R>,
+) -> Vec<Option<String>> {
+    // before DWARF 5 file indexes start at 1 and 0 means no file
+    let first = if header.version() >= 5 { 0 } else { 1 };
+    let count = header.file_names().len() as u64;
+    let mut files = vec![None; first as usize];
+    files.extend((first..first + count).map(|val| resolve_file(dwarf, unit, header, val)));
+    files
+}
+// Get a string attribute of a file entry, e.g. its path or directory
+fn file_entry_string<R: Reader>(
+    dwarf: &gimli::Dwarf<R>,
+    unit: &gimli::Unit<R>,
+    value: gimli::AttributeValue<R>,
+) -> Option<String> {
+    let s = dwarf.attr_string(unit, value).ok()?;
+    Some(s.to_string().ok()?.to_string())
+}
+// Get the path of a DW_AT_decl_file index from the unit's line program.
+// header.file() takes care of the index meaning something different in
+// DWARF 5, and the strings can use any form, DWARF 5 normally puts them in
+// .debug_line_str. Entries can also carry an MD5 checksum, which isn't needed.
+fn resolve_file<R: Reader>(
+    dwarf: &gimli::Dwarf<R>,
+    unit: &gimli::Unit<R>,
+    header: &gimli::LineProgramHeader<R>,
+    val: u64,
+) -> Option<String> {
+    let f = header.file(val)?;
+    let file_str = file_entry_string(dwarf, unit, f.path_name())?;
+    let dir_str = f
+        .directory(header)
+        .and_then(|dir| file_entry_string(dwarf, unit, dir));
+    // the module file is written as the unit names it. That is file 1 before
+    // DWARF 5, and file 0 (which is often repeated as file 1) from DWARF 5 on.
+    let is_module_file = if header.version() >= 5 {
+        let module = header.file(0)?;
+        val == 0
+            || (file_entry_string(dwarf, unit, module.path_name()).as_ref() == Some(&file_str)
+                && module.directory_index() == f.directory_index())
+    } else {
+        val == 1
+    };
+    if is_module_file {
+        if let Some(name) = &unit.name {
+            return Some(name.to_string().ok()?.to_string());
+        }
+    }
+    let path = match dir_str {
+        Some(dir) => std::path::Path::new(&dir).join(&file_str),
+        None => std::path::PathBuf::from(&file_str),
+    };
+    Some(path.to_str()?.to_string())
+}
+// Get the offset of a reference attribute, only references into the same
+// unit can be followed
+fn same_unit_offset<R: Reader>(
+    unit: &gimli::Unit<R>,
+    value: gimli::AttributeValue<R>,
+) -> Option<gimli::UnitOffset> {
+    match value {
+        gimli::AttributeValue::UnitRef(offset) => Some(offset),
+        gimli::AttributeValue::DebugInfoRef(offset) => {
+            gimli::UnitSectionOffset::DebugInfoOffset(offset).to_unit_offset(unit)
+        }
+        _otherwise => None,
+    }
+}
+fn read_decl_attrs<R: Reader>(
+    dwarf: &gimli::Dwarf<R>,
+    unit: &gimli::Unit<R>,
+    unit_info: &UnitInfo,
+    entry: &gimli::DebuggingInformationEntry<R>,
+    stats: &mut Stats,
+) -> DeclAttrs {
+    let mut decl = DeclAttrs::default();
+    let mut attrs = entry.attrs();
+    // Constants are read through attr.value(), which has the value of a
+    // DW_FORM_implicit_const (kept in the abbreviation rather than the DIE,
+    // as GCC's DWARF 5 does for decl_file and decl_column) the same as any
+    // other constant form
+    loop {
+        let attr = match attrs.next() {
+            Ok(Some(attr)) => attr,
+            Ok(None) => break,
+            Err(e) => {
+                stats.warn(&format!(
+                    "could not read the attributes of the DIE at 0x{:x}: {}, using those before the damage",
+                    die_offset(unit, entry),
+                    e
+                ));
+                break;
+            }
+        };
+        match attr.name() {
+            gimli::DW_AT_name => {
+                // the name can be inline (DW_FORM_string) or in .debug_str,
+                // bytes that aren't UTF-8 are replaced
+                if let Ok(name) = dwarf.attr_string(unit, attr.value()) {
+                    decl.func_name = name.to_string_lossy().ok().map(|n| n.to_string());
+                }
+            }
+            gimli::DW_AT_linkage_name | gimli::DW_AT_MIPS_linkage_name => {
+                if let Ok(name) = dwarf.attr_string(unit, attr.value()) {
+                    decl.linkage_name = name.to_string().ok().map(|n| n.to_string());
+                }
+            }
+            gimli::DW_AT_decl_file => {
+                if let gimli::AttributeValue::FileIndex(val) = attr.value() {
+                    decl.file_name = unit_info.files.get(val as usize).cloned().flatten();
+                }
+            }
+            gimli::DW_AT_decl_line => {
+                decl.line_number = attr.value().udata_value();
+            }
+            gimli::DW_AT_decl_column => {
+                decl.column_number = attr.value().udata_value();
+            }
+            gimli::DW_AT_visibility => {
+                if let gimli::AttributeValue::Visibility(vis) = attr.value() {
+                    decl.visibility = Some(vis);
+                }
+            }
+            gimli::DW_AT_low_pc => {
+                decl.low_pc = dwarf.attr_address(unit, attr.value()).ok().flatten();
+            }
+            gimli::DW_AT_abstract_origin | gimli::DW_AT_specification => {
+                let kind = if attr.name() == gimli::DW_AT_abstract_origin {
+                    OriginKind::AbstractOrigin
+                } else {
+                    OriginKind::Specification
+                };
+                decl.origin = same_unit_offset(unit, attr.value()).map(|o| (kind, o));
+                decl.other_origin = match (decl.origin, attr.value()) {
+                    (Some(_), _) => None,
+                    (None, gimli::AttributeValue::DebugInfoRef(offset)) => {
+                        Some((kind, OtherUnitRef::Info(offset)))
+                    }
+                    (None, gimli::AttributeValue::DebugTypesRef(signature)) => {
+                        Some((kind, OtherUnitRef::Signature(signature)))
+                    }
+                    (None, gimli::AttributeValue::DebugInfoRefSup(offset)) => {
+                        Some((kind, OtherUnitRef::Sup(offset)))
+                    }
+                    _otherwise => None,
+                };
+            }
+            gimli::DW_AT_import => {
+                if let Some(offset) = same_unit_offset(unit, attr.value()) {
+                    decl.origin = Some((OriginKind::Import, offset));
+                }
+            }
+            gimli::DW_AT_type => {
+                decl.type_offset = same_unit_offset(unit, attr.value());
+            }
+            gimli::DW_AT_declaration => {
+                decl.declaration = matches!(attr.value(), gimli::AttributeValue::Flag(true));
+            }
+            gimli::DW_AT_prototyped => {
+                decl.prototyped = matches!(attr.value(), gimli::AttributeValue::Flag(true));
+            }
+            gimli::DW_AT_reference | gimli::DW_AT_rvalue_reference => {
+                if matches!(attr.value(), gimli::AttributeValue::Flag(true)) {
+                    decl.ref_qualifier = Some(if attr.name() == gimli::DW_AT_reference {
+                        "&"
+                    } else {
+                        "&&"
+                    });
+                }
+            }
+            _otherwise => {}
+        }
+    }
+    decl
+}
+// Get the DW_AT_language of a unit's root DIE
+fn unit_language<R: Reader>(unit: &gimli::Unit<R>) -> Option<gimli::DwLang> {
+    let mut entries = unit.entries();
+    let (_, root) = entries.next_dfs().ok()??;
+    match root.attr_value(gimli::DW_AT_language).ok()?? {
+        gimli::AttributeValue::Language(lang) => Some(lang),
+        _otherwise => None,
+    }
+}
+fn is_c(language: Option<gimli::DwLang>) -> bool {
+    matches!(
+        language,
+        Some(gimli::DW_LANG_C89)
+            | Some(gimli::DW_LANG_C)
+            | Some(gimli::DW_LANG_C99)
+            | Some(gimli::DW_LANG_C11)
+            | Some(gimli::DW_LANG_C17)
+    )
+}
+fn is_ada(language: Option<gimli::DwLang>) -> bool {
+    matches!(
+        language,
+        Some(gimli::DW_LANG_Ada83)
+            | Some(gimli::DW_LANG_Ada95)
+            | Some(gimli::DW_LANG_Ada2005)
+            | Some(gimli::DW_LANG_Ada2012)
+    )
+}
+// Capitalise each word of a lower case GNAT name, "my_proc" -> "My_Proc"
+fn ada_mixed_case(name: &str) -> String {
+    name.split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(c) => c.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join("_")
+}
+// GNAT flattens "Pkg.Child.Proc" into "pkg__child__proc", split that back into
+// the package and the subprogram name. With convert the names are turned back
+// into Ada mixed case and the packages joined with separator, otherwise the
+// raw subprogram name is kept.
+fn split_ada_name(name: &str, convert: bool, separator: &str) -> (String, Option<String>) {
+    // the main subprogram of a library is emitted as _ada_<name>
+    let name = if convert {
+        name.strip_prefix("_ada_").unwrap_or(name)
+    } else {
+        name
+    };
+    let parts: Vec<&str> = name.split("__").collect();
+    if parts.len() < 2 || parts.iter().any(|p| p.is_empty()) {
+        let name = if convert {
+            ada_mixed_case(name)
+        } else {
+            name.to_string()
+        };
+        return (name, None);
+    }
+    let (last, packages) = parts.split_last().unwrap();
+    if convert {
+        let packages: Vec<String> = packages.iter().map(|p| ada_mixed_case(p)).collect();
+        (ada_mixed_case(last), Some(packages.join(separator)))
+    } else {
+        (name.to_string(), Some(packages.join("__")))
+    }
+}
+// The Go compiler names functions by their import path, "net/http.Get",
+// methods by their receiver, "net/http.(*Client).Do", and closures by the
+// function they are in, "main.run.func1". Split that into the name and its
+// scope, joining the scope's parts with separator.
+fn split_go_name(name: &str, separator: &str) -> (String, Option<(Kind, String)>) {
+    let path_end = name.rfind('/').map_or(0, |i| i + 1);
+    let Some(dot) = name[path_end..].find('.').map(|i| path_end + i) else {
+        return (name.to_string(), None);
+    };
+    let (package, rest) = (&name[..dot], &name[dot + 1..]);
+    let Some((outer, inner)) = rest.split_once('.') else {
+        return (rest.to_string(), Some((Kind::Package, package.to_string())));
+    };
+    let is_closure = inner.starts_with("func")
+        && inner[4..]
+            .split('.')
+            .all(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()));
+    let kind = if is_closure {
+        Kind::Function
+    } else {
+        Kind::Type
+    };
+    let outer = outer.trim_start_matches("(*").trim_start_matches('(');
+    let outer = outer.trim_end_matches(')');
+    let scope = format!("{}{}{}", package, separator, outer);
+    (inner.to_string(), Some((kind, scope)))
+}
+// Rust linkage names already demangled while reading a file, in the "{:#}"
+// form, None for names that aren't Rust mangled. A generic function is
+// instantiated in every codegen unit that uses it, so the same names come up
+// again and again.
+type Demangled = HashMap<String, Option<String>>;
+// Demangle a Rust linkage name without its hash, each name only once
+fn demangle_rust(linkage_name: &str, demangled: &mut Demangled) -> Option<String> {
+    if let Some(cached) = demangled.get(linkage_name) {
+        return cached.clone();
+    }
+    let result = rustc_demangle::try_demangle(linkage_name)
+        .ok()
+        .map(|d| format!("{:#}", d));
+    demangled.insert(linkage_name.to_string(), result.clone());
+    result
+}
+// The trait impl a Rust method is in, from its demangled linkage name,
+// "<foo::Foo as foo::Bar>::method" -> "foo::Bar for foo::Foo". The brackets
+// are counted, so the type and trait can be generic. None for functions that
+// aren't in a trait impl.
+fn rust_trait_impl(linkage_name: &str, demangled: &mut Demangled) -> Option<String> {
+    let demangled = demangle_rust(linkage_name, demangled)?;
+    let inner = demangled.strip_prefix('<')?;
+    let mut depth = 0;
+    let mut split = None;
+    let mut end = None;
+    let mut previous = ' ';
+    for (i, c) in inner.char_indices() {
+        match c {
+            '<' | '(' | '[' => depth += 1,
+            // the arrow of a fn type
+            '>' if previous == '-' => {}
+            '>' | ')' | ']' if depth == 0 => {
+                end = Some(i);
+                break;
+            }
+            '>' | ')' | ']' => depth -= 1,
+            ' ' if depth == 0 && split.is_none() && inner[i..].starts_with(" as ") => {
+                split = Some(i)
+            }
+            _ => {}
+        }
+        previous = c;
+    }
+    let (split, end) = (split?, end?);
+    Some(format!(
+        "{} for {}",
+        &inner[split + " as ".len()..end],
+        &inner[..split]
+    ))
+}
+// The last part of the demangled linkage name of a shim rustc generated
+// around a function, e.g. for calling a closure through a vtable:
+// "{shim:vtable#0}" with v0 mangling, "{{vtable.shim}}" with legacy mangling.
+// None for other functions. A shim has the DW_AT_name of what it wraps.
+fn rust_shim(linkage_name: &str, demangled: &mut Demangled) -> Option<String> {
+    let demangled = demangle_rust(linkage_name, demangled)?;
+    let last = demangled.rsplit("::").next()?;
+    let shim = last.starts_with("{shim:") || last.ends_with(".shim}}") || last.ends_with("-shim}}");
+    shim.then(|| last.to_string())
+}
+// --rust-closures qualify: name a closure after the function it is in,
+// "run::{closure#0}" rather than "{closure#0}", and a closure in a closure
+// after both. Those are the last parts of its scope.
+fn qualify_rust_closure(func_name: &str, scope: Option<&str>, separator: &str) -> String {
+    let Some(scope) = scope else {
+        return func_name.to_string();
+    };
+    let parts: Vec<&str> = scope.split(separator).collect();
+    let closures = parts
+        .iter()
+        .rev()
+        .take_while(|p| p.starts_with("{closure#"))
+        .count();
+    let first = parts.len().saturating_sub(closures + 1);
+    let mut names = parts[first..].to_vec();
+    names.push(func_name);
+    names.join(separator)
+}
+fn is_go(language: Option<gimli::DwLang>) -> bool {
+    language == Some(gimli::DW_LANG_Go)
+}
+// How the parts of a scope are joined: --scope-separator, or what the unit's
+// language writes
+fn scope_separator(language: Option<gimli::DwLang>, options: &Options) -> &str {
+    if let Some(separator) = &options.scope_separator {
+        return separator;
+    }
+    match kind_language(language) {
+        Some("ada") | Some("fortran") | Some("go") => ".",
+        Some(_) => "::",
+        None => match language {
+            Some(gimli::DW_LANG_Java)
+            | Some(gimli::DW_LANG_D)
+            | Some(gimli::DW_LANG_Python)
+            | Some(gimli::DW_LANG_Modula2)
+            | Some(gimli::DW_LANG_Modula3)
+            | Some(gimli::DW_LANG_Pascal83)
+            | Some(gimli::DW_LANG_Swift)
+            | Some(gimli::DW_LANG_Julia)
+            | Some(gimli::DW_LANG_Kotlin)
+            | Some(gimli::DW_LANG_Zig) => ".",
+            _otherwise => "::",
+        },
+    }
+}
+// The namespaces, classes, structs and unions around the declarations of a
+// unit. Each scope is stored once, the declarations point at theirs.
+// Anonymous ones add nothing to the scope, unless anonymous_by_file: then
+// they are written as <anonymous> after the unit's file name, so what is
+// local to one translation unit isn't in the same scope as that of another.
+#[derive(Default)]
+struct UnitScopes {
+    // the kind of the innermost part, and the names from the outside in
+    scopes: Vec<(Kind, Vec<String>)>,
+    by_offset: HashMap<gimli::UnitOffset, usize>,
+}
+fn unit_scopes<R: Reader>(
+    dwarf: &gimli::Dwarf<R>,
+    unit: &gimli::Unit<R>,
+    anonymous_by_file: bool,
+) -> UnitScopes {
+    let mut unit_scopes = UnitScopes::default();
+    let unit_name = unit
+        .name
+        .as_ref()
+        .and_then(|n| Some(n.to_string().ok()?.to_string()));
+    let mut entries = unit.entries();
+    // the scope each enclosing DIE gives its children; None inside
+    // functions, whose locals aren't tagged
+    let mut parents: Vec<Option<usize>> = Vec::new();
+    // the scope of each namespace or type DIE, for the definitions that
+    // refer back to them
+    let mut scope_dies: HashMap<gimli::UnitOffset, usize> = HashMap::new();
+    let mut depth = 0;
+    while let Ok(Some((delta, entry))) = entries.next_dfs() {
+        depth += delta;
+        parents.truncate(depth as usize);
+        let enclosing = parents.last().copied().flatten();
+        let kind = match entry.tag() {
+            gimli::DW_TAG_namespace => Some(Kind::Namespace),
+            gimli::DW_TAG_class_type => Some(Kind::Class),
+            gimli::DW_TAG_structure_type => Some(Kind::Struct),
+            gimli::DW_TAG_union_type => Some(Kind::Union),
+            _otherwise => None,
+        };
+        let name = entry
+            .attr_value(gimli::DW_AT_name)
+            .ok()
+            .flatten()
+            .and_then(|v| dwarf.attr_string(unit, v).ok())
+            .and_then(|n| Some(n.to_string().ok()?.to_string()));
+        let specification = entry
+            .attr_value(gimli::DW_AT_specification)
+            .ok()
+            .flatten()
+            .and_then(|v| same_unit_offset(unit, v))
+            .and_then(|o| scope_dies.get(&o).copied());
+        let scope = match (entry.tag(), kind, name) {
+            // a type defined outside the namespace it is declared in, as
+            // type units do, is in the scope of its declaration
+            (_, Some(_), _) if specification.is_some() => specification,
+            (_, Some(kind), Some(name)) => {
+                let mut names = enclosing.map_or(Vec::new(), |i| unit_scopes.scopes[i].1.clone());
+                names.push(name);
+                unit_scopes.scopes.push((kind, names));
+                Some(unit_scopes.scopes.len() - 1)
+            }
+            (_, Some(kind), None) if anonymous_by_file => {
+                let mut names = enclosing.map_or(Vec::new(), |i| unit_scopes.scopes[i].1.clone());
+                names.extend(unit_name.clone());
+                names.push("<anonymous>".to_string());
+                unit_scopes.scopes.push((kind, names));
+                Some(unit_scopes.scopes.len() - 1)
+            }
+            (_, Some(_), None) => enclosing,
+            (gimli::DW_TAG_compile_unit, _, _) | (gimli::DW_TAG_partial_unit, _, _) => None,
+            (gimli::DW_TAG_subprogram, _, _)
+            | (gimli::DW_TAG_variable, _, _)
+            | (gimli::DW_TAG_member, _, _) => {
+                if let Some(i) = enclosing {
+                    unit_scopes.by_offset.insert(entry.offset(), i);
+                }
+                None
+            }
+            _otherwise => None,
+        };
+        if let (Some(_), Some(i)) = (kind, scope) {
+            scope_dies.insert(entry.offset(), i);
+        }
+        parents.push(scope);
+    }
+    unit_scopes
+}
+// The DIE a definition or instance was declared by, following its
+// specification and abstract origin
+fn declaration_offset<R: Reader>(
+    unit: &gimli::Unit<R>,
+    entry: &gimli::DebuggingInformationEntry<R>,
+    max_depth: usize,
+) -> gimli::UnitOffset {
+    let mut offset = entry.offset();
+    let mut next = entry.clone();
+    for _ in 0..max_depth {
+        let origin = [gimli::DW_AT_specification, gimli::DW_AT_abstract_origin]
+            .iter()
+            .find_map(|name| next.attr_value(*name).ok().flatten())
+            .and_then(|v| same_unit_offset(unit, v));
+        let Some(origin) = origin else {
+            break;
+        };
+        let Ok(origin_entry) = unit.entry(origin) else {
+            break;
+        };
+        offset = origin;
+        next = origin_entry;
+    }
+    offset
+}
+// The units of a file, for following references out of the unit being
+// walked: by where they start in .debug_info, and type units, which can also
+// be in .debug_types, by their signature. A unit is only parsed once a
+// reference points into it.
+struct OtherUnits<R: Reader> {
+    info_units: Vec<gimli::UnitHeader<R>>,
+    type_units: HashMap<gimli::DebugTypeSignature, gimli::UnitHeader<R>>,
+    // the units of the supplementary file, if there is one
+    sup_units: Vec<gimli::UnitHeader<R>>,
+    loaded: Vec<OtherUnit<R>>,
+    // by whether the unit is in the supplementary file, and its start
+    by_start: HashMap<(bool, gimli::UnitSectionOffset), usize>,
+    // --group-anonymous-by-file, for the scopes of the loaded units
+    anonymous_by_file: bool,
+}
+struct OtherUnit<R: Reader> {
+    unit: gimli::Unit<R>,
+    info: UnitInfo,
+    scopes: UnitScopes,
+    // a unit of the supplementary file, read with its sections
+    sup: bool,
+}
+impl<R: Reader> OtherUnits<R> {
+    fn new(dwarf: &gimli::Dwarf<R>, anonymous_by_file: bool) -> Self {
+        let mut other_units = OtherUnits {
+            info_units: Vec::new(),
+            type_units: HashMap::new(),
+            sup_units: Vec::new(),
+            loaded: Vec::new(),
+            by_start: HashMap::new(),
+            anonymous_by_file,
+        };
+        let mut add = |header: gimli::UnitHeader<R>| {
+            if let gimli::UnitType::Type { type_signature, .. }
+            | gimli::UnitType::SplitType { type_signature, .. } = header.type_()
+            {
+                other_units.type_units.insert(type_signature, header);
+            }
+        };
+        let mut units = dwarf.units();
+        while let Ok(Some(header)) = units.next() {
+            add(header.clone());
+            other_units.info_units.push(header);
+        }
+        let mut units = dwarf.type_units();
+        while let Ok(Some(header)) = units.next() {
+            add(header);
+        }
+        if let Some(sup) = dwarf.sup() {
+            let mut units = sup.units();
+            while let Ok(Some(header)) = units.next() {
+                other_units.sup_units.push(header);
+            }
+        }
+        other_units
+    }
+    // The DWARF a loaded unit is read with, the supplementary file's for
+    // its units
+    fn dwarf<'a>(&self, dwarf: &'a gimli::Dwarf<R>, in_unit: Option<usize>) -> &'a gimli::Dwarf<R> {
+        match in_unit {
+            Some(i) if self.loaded[i].sup => dwarf.sup().unwrap_or(dwarf),
+            _otherwise => dwarf,
+        }
+    }
+    // The loaded unit a reference points into, and the DIE's offset in it.
+    // A signature points at the type of its type unit.
+    fn resolve(
+        &mut self,
+        dwarf: &gimli::Dwarf<R>,
+        target: OtherUnitRef,
+    ) -> Option<(usize, gimli::UnitOffset)> {
+        // the unit an offset into .debug_info is in
+        let containing = |units: &[gimli::UnitHeader<R>], offset: gimli::DebugInfoOffset| {
+            let end =
+                units.partition_point(|h| h.offset() <= gimli::UnitSectionOffset::from(offset));
+            let header = units.get(end.checked_sub(1)?)?.clone();
+            let offset = offset.to_unit_offset(&header)?;
+            Some((header, offset))
+        };
+        let (header, offset) = match target {
+            OtherUnitRef::Info(offset) => containing(&self.info_units, offset)?,
+            OtherUnitRef::Sup(offset) => containing(&self.sup_units, offset)?,
+            OtherUnitRef::Signature(signature) => {
+                let header = self.type_units.get(&signature)?.clone();
+                let type_offset = match header.type_() {
+                    gimli::UnitType::Type { type_offset, .. }
+                    | gimli::UnitType::SplitType { type_offset, .. } => type_offset,
+                    _otherwise => return None,
+                };
+                (header, type_offset)
+            }
+        };
+        let sup = matches!(target, OtherUnitRef::Sup(_));
+        let start = (sup, header.offset());
+        let i = match self.by_start.get(&start) {
+            Some(&i) => i,
+            None => {
+                let dwarf = if sup { dwarf.sup()? } else { dwarf };
+                let unit = dwarf.unit(header).ok()?;
+                let info = unit_info(dwarf, &unit);
+                let scopes = unit_scopes(dwarf, &unit, self.anonymous_by_file);
+                self.loaded.push(OtherUnit {
+                    unit,
+                    info,
+                    scopes,
+                    sup,
+                });
+                self.by_start.insert(start, self.loaded.len() - 1);
+                self.loaded.len() - 1
+            }
+        };
+        Some((i, offset))
+    }
+    // The scope of a declaration in one of the loaded units
+    fn scope(
+        &self,
+        declaration: Option<(usize, gimli::UnitOffset)>,
+        separator: &str,
+    ) -> Option<(Kind, String)> {
+        let (i, offset) = declaration?;
+        let scopes = &self.loaded[i].scopes;
+        let (kind, names) = &scopes.scopes[*scopes.by_offset.get(&offset)?];
+        Some((*kind, names.join(separator)))
+    }
+}
+// Where the abstract origin or specification of a DIE is: in the unit the DIE
+// is in (in_unit, None for the unit being walked) or in another one
+fn origin_target<R: Reader>(
+    dwarf: &gimli::Dwarf<R>,
+    other_units: &mut OtherUnits<R>,
+    in_unit: Option<usize>,
+    decl: &DeclAttrs,
+) -> Option<(OriginKind, Option<usize>, gimli::UnitOffset)> {
+    if let Some((kind, offset)) = decl.origin {
+        return Some((kind, in_unit, offset));
+    }
+    let (kind, target) = decl.other_origin?;
+    // a unit of the supplementary file refers to its own .debug_info
+    let target = match target {
+        OtherUnitRef::Info(offset) if in_unit.is_some_and(|i| other_units.loaded[i].sup) => {
+            OtherUnitRef::Sup(offset)
+        }
+        target => target,
+    };
+    let (i, offset) = other_units.resolve(dwarf, target)?;
+    Some((kind, Some(i), offset))
+}
+// A using declaration is tagged at the import site, with the name of the
+// imported entity, or its own name for a namespace alias
+fn process_imported_declaration<R: Reader>(
+    dwarf: &gimli::Dwarf<R>,
+    unit: &gimli::Unit<R>,
+    unit_info: &UnitInfo,
+    entry: &gimli::DebuggingInformationEntry<R>,
+    stats: &mut Stats,
+) -> Option<FunctionInfo> {
+    let decl = read_decl_attrs(dwarf, unit, unit_info, entry, stats);
+    let func_name = match (decl.func_name, decl.origin) {
+        (Some(alias), _) => alias,
+        (None, Some((OriginKind::Import, offset))) => {
+            let imported = unit.entry(offset).ok()?;
+            read_decl_attrs(dwarf, unit, unit_info, &imported, stats).func_name?
+        }
+        (None, _) => return None,
+    };
+    Some(FunctionInfo {
+        func_name,
+        file_name: decl.file_name?,
+        line_number: decl.line_number?,
+        column_number: decl.column_number.unwrap_or(0),
+        kind: Kind::Imported,
+        scope: None,
+        fields: Vec::new(),
+        comp_dir: unit_info.comp_dir.clone(),
+    })
+}
+// The lower bound of a subrange without DW_AT_lower_bound: 1 in Ada, Fortran
+// and the other languages --kinds doesn't know that count from 1, 0 in the
+// rest and in units that don't say
+fn default_lower_bound(language: Option<gimli::DwLang>) -> i64 {
+    match kind_language(language) {
+        Some("ada") | Some("fortran") => 1,
+        Some(_) => 0,
+        None => match language {
+            Some(gimli::DW_LANG_Cobol74)
+            | Some(gimli::DW_LANG_Cobol85)
+            | Some(gimli::DW_LANG_Modula2)
+            | Some(gimli::DW_LANG_Modula3)
+            | Some(gimli::DW_LANG_Pascal83)
+            | Some(gimli::DW_LANG_PLI)
+            | Some(gimli::DW_LANG_Julia) => 1,
+            _otherwise => 0,
+        },
+    }
+}
+// The [N] of each DW_TAG_subrange_type child of an array type, from
+// DW_AT_count or DW_AT_upper_bound. Bounds that aren't constant, like those
+// of variable length arrays, or that are missing are written as [].
+fn array_dimensions<R: Reader>(
+    unit: &gimli::Unit<R>,
+    offset: gimli::UnitOffset,
+    language: Option<gimli::DwLang>,
+) -> Option<String> {
+    let mut tree = unit.entries_tree(Some(offset)).ok()?;
+    let root = tree.root().ok()?;
+    let mut children = root.children();
+    let mut dims = String::new();
+    while let Some(child) = children.next().ok()? {
+        let entry = child.entry();
+        if entry.tag() != gimli::DW_TAG_subrange_type {
+            continue;
+        }
+        let constant = |name| -> Option<i64> {
+            match entry.attr_value(name).ok()?? {
+                gimli::AttributeValue::Sdata(v) => Some(v),
+                value => value.udata_value().map(|v| v as i64),
+            }
+        };
+        let default_lower = default_lower_bound(language);
+        let count = constant(gimli::DW_AT_count).or_else(|| {
+            let upper = constant(gimli::DW_AT_upper_bound)?;
+            Some(upper - constant(gimli::DW_AT_lower_bound).unwrap_or(default_lower) + 1)
+        });
+        match count {
+            Some(n) if n > 0 => dims.push_str(&format!("[{}]", n)),
+            _ => dims.push_str("[]"),
+        }
+    }
+    if dims.is_empty() {
+        dims.push_str("[]");
+    }
+    Some(dims)
+}
+// Write out the type a DW_AT_type refers to the way C declares it, e.g.
+// "const char *" or "int[10]". The chain of modifiers is bounded like the
+// references of a DIE are. Types that can't be written are left out.
+fn type_name<R: Reader>(
+    dwarf: &gimli::Dwarf<R>,
+    unit: &gimli::Unit<R>,
+    language: Option<gimli::DwLang>,
+    offset: Option<gimli::UnitOffset>,
+    depth: usize,
+) -> Option<String> {
+    let Some(offset) = offset else {
+        // a pointer without a DW_AT_type points to void
+        return Some("void".to_string());
+    };
+    if depth == 0 {
+        return None;
+    }
+    let entry = unit.entry(offset).ok()?;
+    let name = || -> Option<String> {
+        let value = entry.attr_value(gimli::DW_AT_name).ok()??;
+        Some(
+            dwarf
+                .attr_string(unit, value)
+                .ok()?
+                .to_string()
+                .ok()?
+                .to_string(),
+        )
+    };
+    let inner = || {
+        let next = entry
+            .attr_value(gimli::DW_AT_type)
+            .ok()
+            .flatten()
+            .and_then(|v| same_unit_offset(unit, v));
+        type_name(dwarf, unit, language, next, depth - 1)
+    };
+    match entry.tag() {
+        gimli::DW_TAG_base_type
+        | gimli::DW_TAG_typedef
+        | gimli::DW_TAG_structure_type
+        | gimli::DW_TAG_union_type
+        | gimli::DW_TAG_enumeration_type
+        | gimli::DW_TAG_class_type => name(),
+        gimli::DW_TAG_pointer_type => Some(format!("{} *", inner()?)),
+        gimli::DW_TAG_reference_type => Some(format!("{} &", inner()?)),
+        gimli::DW_TAG_rvalue_reference_type => Some(format!("{} &&", inner()?)),
+        gimli::DW_TAG_const_type => Some(format!("const {}", inner()?)),
+        gimli::DW_TAG_volatile_type => Some(format!("volatile {}", inner()?)),
+        gimli::DW_TAG_array_type => Some(format!(
+            "{}{}",
+            inner()?,
+            array_dimensions(unit, offset, language)?
+        )),
+        _otherwise => None,
+    }
+}
+// The cv-qualifiers of the class an object parameter (`this`) points to,
+// " const" for a const member function. The pointer itself is often const
+// too, that doesn't make the function const.
+fn object_qualifiers<R: Reader>(unit: &gimli::Unit<R>, offset: gimli::UnitOffset) -> String {
+    let mut qualifiers = String::new();
+    let mut next = Some(offset);
+    let mut pointee = false;
+    // const volatile X *const volatile is as deep as it gets
+    for _ in 0..6 {
+        let Some(entry) = next.and_then(|o| unit.entry(o).ok()) else {
+            break;
+        };
+        match entry.tag() {
+            gimli::DW_TAG_pointer_type if !pointee => pointee = true,
+            gimli::DW_TAG_const_type if pointee => qualifiers.insert_str(0, " const"),
+            gimli::DW_TAG_volatile_type if pointee => qualifiers.push_str(" volatile"),
+            gimli::DW_TAG_const_type | gimli::DW_TAG_volatile_type => {}
+            _otherwise => break,
+        }
+        next = entry
+            .attr_value(gimli::DW_AT_type)
+            .ok()
+            .flatten()
+            .and_then(|v| same_unit_offset(unit, v));
+    }
+    qualifiers
+}
+// The parameter types of a function the way C++ writes them,
+// "(int, const char *)", followed by the cv- and ref-qualifiers of a member
+// function, "() const &&", so overloads that only differ in those get
+// different signatures. The names are left out, declarations in a class
+// don't have them and the declaration and definition should agree. None
+// when a parameter's type can't be written.
+fn signature<R: Reader>(
+    dwarf: &gimli::Dwarf<R>,
+    unit: &gimli::Unit<R>,
+    language: Option<gimli::DwLang>,
+    offset: gimli::UnitOffset,
+    ref_qualifier: Option<&str>,
+    depth: usize,
+) -> Option<String> {
+    let mut tree = unit.entries_tree(Some(offset)).ok()?;
+    let root = tree.root().ok()?;
+    let mut children = root.children();
+    let mut params = Vec::new();
+    let mut qualifiers = String::new();
+    while let Some(child) = children.next().ok()? {
+        let param = child.entry();
+        match param.tag() {
+            gimli::DW_TAG_formal_parameter => {
+                let type_offset = param
+                    .attr_value(gimli::DW_AT_type)
+                    .ok()
+                    .flatten()
+                    .and_then(|v| same_unit_offset(unit, v));
+                if let Ok(Some(gimli::AttributeValue::Flag(true))) =
+                    param.attr_value(gimli::DW_AT_artificial)
+                {
+                    // the object parameter, written as the qualifiers
+                    qualifiers = object_qualifiers(unit, type_offset?);
+                    continue;
+                }
+                params.push(type_name(dwarf, unit, language, Some(type_offset?), depth)?);
+            }
+            gimli::DW_TAG_unspecified_parameters => params.push("...".to_string()),
+            _otherwise => {}
+        }
+    }
+    if let Some(ref_qualifier) = ref_qualifier {
+        qualifiers.push(' ');
+        qualifiers.push_str(ref_qualifier);
+    }
+    Some(format!("({}){}", params.join(", "), qualifiers))
+}
+// A variable at file or namespace scope. Declarations are left out, the
+// definition is tagged instead, taking what it leaves out from the
+// declaration its DW_AT_specification points to.
+fn process_variable<R: Reader>(
+    dwarf: &gimli::Dwarf<R>,
+    unit: &gimli::Unit<R>,
+    unit_info: &UnitInfo,
+    entry: &gimli::DebuggingInformationEntry<R>,
+    options: &Options,
+    other_units: &mut OtherUnits<R>,
+    stats: &mut Stats,
+) -> Option<FunctionInfo> {
+    let mut decl = read_decl_attrs(dwarf, unit, unit_info, entry, stats);
+    if decl.declaration {
+        return None;
+    }
+    // the type is looked up in the unit it was found in
+    let mut type_unit = None;
+    let mut declared_in = None;
+    if let Some((OriginKind::Specification, in_unit, offset)) =
+        origin_target(dwarf, other_units, None, &decl)
+    {
+        let (spec_unit, spec_info) = match in_unit {
+            Some(i) => (&other_units.loaded[i].unit, &other_units.loaded[i].info),
+            None => (unit, unit_info),
+        };
+        let spec_entry = spec_unit.entry(offset).ok()?;
+        let spec_dwarf = other_units.dwarf(dwarf, in_unit);
+        let spec = read_decl_attrs(spec_dwarf, spec_unit, spec_info, &spec_entry, stats);
+        decl.func_name = decl.func_name.or(spec.func_name);
+        decl.file_name = decl.file_name.or(spec.file_name);
+        decl.line_number = decl.line_number.or(spec.line_number);
+        decl.column_number = decl.column_number.or(spec.column_number);
+        if decl.type_offset.is_none() {
+            decl.type_offset = spec.type_offset;
+            type_unit = in_unit;
+        }
+        declared_in = in_unit.map(|i| (i, offset));
+    }
+    let mut fields = Vec::new();
+    if decl.type_offset.is_some() {
+        let language = unit_info.language;
+        let unit = type_unit.map_or(unit, |i| &other_units.loaded[i].unit);
+        let dwarf = other_units.dwarf(dwarf, type_unit);
+        if let Some(name) = type_name(dwarf, unit, language, decl.type_offset, options.max_depth) {
+            fields.push(("typeref", format!("typename:{}", name)));
+        }
+    }
+    let separator = scope_separator(unit_info.language, options);
+    Some(FunctionInfo {
+        func_name: decl.func_name?,
+        file_name: decl.file_name?,
+        line_number: decl.line_number?,
+        column_number: decl.column_number.unwrap_or(0),
+        kind: Kind::Variable,
+        scope: other_units.scope(declared_in, separator),
+        fields,
+        comp_dir: unit_info.comp_dir.clone(),
+    })
+}
+// Drop the extension fields that weren't asked for and, with
+// --include-only-files, the tags of files not in the manifest. matched records
+// which manifest entries were seen. With --normalize-paths the file names
+// are cleaned up as well, and with --strip-template-args-from-scope the
+// scopes.
+fn filter_tags(
+    list: &mut Vec<FunctionInfo>,
+    options: &Options,
+    wanted: Option<&[String]>,
+    matched: &mut [bool],
+) {
+    for f in list.iter_mut() {
+        f.fields.retain(|(name, _)| options.fields.contains(name));
+        if options.normalize_paths {
+            f.file_name = normalize_path(&f.file_name);
+        }
+        if let (true, Some((_, scope))) = (options.strip_template_args, &mut f.scope) {
+            *scope = strip_template_args(scope);
+        }
+    }
+    if let Some(wanted) = wanted {
+        list.retain(|f| {
+            let file = normalize_path(&f.file_name);
+            match wanted.iter().position(|w| *w == file) {
+                Some(i) => {
+                    matched[i] = true;
+                    true
+                }
+                None => false,
+            }
+        });
+    }
+}
+// Remove the template argument lists from a scope,
+// "ns::Foo<int, Bar<char> >::Inner<2>" -> "ns::Foo::Inner". The brackets are
+// counted, so the arguments can have scopes and templates of their own. A
+// scope whose brackets don't match up is kept as it is.
+fn strip_template_args(scope: &str) -> String {
+    let mut stripped = String::with_capacity(scope.len());
+    let mut depth = 0;
+    for c in scope.chars() {
+        match c {
+            '<' => depth += 1,
+            '>' if depth == 0 => return scope.to_string(),
+            '>' => depth -= 1,
+            _ if depth == 0 => stripped.push(c),
+            _ => {}
+        }
+    }
+    if depth != 0 {
+        return scope.to_string();
+    }
+    stripped
+}
+// Walk all of a unit's entries and collect their tags
+#[allow(clippy::too_many_arguments)]
+fn process_unit<R: Reader>(
+    dwarf: &gimli::Dwarf<R>,
+    unit: &gimli::Unit<R>,
+    unit_info: &UnitInfo,
+    symbols: &Symbols,
+    options: &Options,
+    stats: &mut Stats,
+    file_info_list: &mut Vec<FunctionInfo>,
+    other_units: &mut OtherUnits<R>,
+    demangled: &mut Demangled,
+) {
+    // C has no scopes to find, and Ada and Go names carry theirs
+    let scopes = match unit_info.language {
+        language if is_c(language) || is_ada(language) || is_go(language) => UnitScopes::default(),
+        _otherwise => unit_scopes(dwarf, unit, options.group_anonymous_by_file),
+    };
+    let separator = scope_separator(unit_info.language, options);
+    let kinds = unit_kinds(unit_info.language, options);
+    let mut entries = unit.entries();
+    // the tags of the DIEs enclosing the current one
+    let mut parents: Vec<gimli::DwTag> = Vec::new();
+    let mut depth = 0;
+    loop {
+        // a damaged unit keeps the tags of the DIEs before the damage
+        let (delta, entry) = match entries.next_dfs() {
+            Ok(Some(next)) => next,
+            Ok(None) => break,
+            Err(e) => {
+                stats.warn(&format!(
+                    "could not read the unit at 0x{:x}: {}, the rest of its DIEs are left out",
+                    unit_start(unit),
+                    e
+                ));
+                break;
+            }
+        };
+        depth += delta;
+        parents.truncate(depth as usize);
+        let parent = parents.last().copied();
+        parents.push(entry.tag());
+        let func_info = match entry.tag() {
+            gimli::DW_TAG_subprogram => {
+                // A function
+                stats.subprograms += 1;
+                process_subprogram(
+                    dwarf,
+                    unit,
+                    unit_info,
+                    entry,
+                    symbols,
+                    options,
+                    other_units,
+                    demangled,
+                    stats,
+                )
+            }
+            gimli::DW_TAG_imported_declaration if kinds.contains(&Kind::Imported) => {
+                // A C++ using declaration
+                process_imported_declaration(dwarf, unit, unit_info, entry, stats)
+            }
+            gimli::DW_TAG_variable
+                if kinds.contains(&Kind::Variable)
+                    && matches!(
+                        parent,
+                        Some(gimli::DW_TAG_compile_unit)
+                            | Some(gimli::DW_TAG_partial_unit)
+                            | Some(gimli::DW_TAG_namespace)
+                    ) =>
+            {
+                // locals live inside a subprogram or lexical block
+                process_variable(dwarf, unit, unit_info, entry, options, other_units, stats)
+            }
+            _otherwise => None,
+        };
+        if let Some(mut f) = func_info.filter(|f| kinds.contains(&f.kind)) {
+            if f.scope.is_none() && !scopes.by_offset.is_empty() {
+                let declaration = declaration_offset(unit, entry, options.max_depth);
+                if let Some(&i) = scopes.by_offset.get(&declaration) {
+                    let (kind, names) = &scopes.scopes[i];
+                    f.scope = Some((*kind, names.join(separator)));
+                }
+            }
+            let closure = unit_info.language == Some(gimli::DW_LANG_Rust)
+                && entry.tag() == gimli::DW_TAG_subprogram
+                && f.func_name.starts_with("{closure#");
+            match (closure, options.rust_closures) {
+                (true, RustClosures::Skip) => continue,
+                (true, RustClosures::Qualify) => {
+                    let scope = f.scope.as_ref().map(|(_, s)| s.as_str());
+                    f.func_name = qualify_rust_closure(&f.func_name, scope, separator);
+                }
+                _ => {}
+            }
+            if options.fields.contains(&"dwarftag") {
+                f.fields.push(("dwarftag", entry.tag().to_string()));
+            }
+            file_info_list.push(f);
+        }
+    }
+}
+// The macro sections, which gimli doesn't read: .debug_macro (DWARF 5, and
+// GNU's extension of DWARF 4) and .debug_macinfo from before that
+struct MacroSections<R: Reader> {
+    debug_macro: R,
+    debug_macinfo: R,
+    // a .o file keeps each imported list in a .debug_macro section of its
+    // own, in a COMDAT group, only the first of which is loaded, so imports
+    // can't be followed
+    follow_imports: bool,
+}
+// The name a macro definition defines, "MAX(a, b) ((a) > (b) ? (a) : (b))"
+// -> "MAX"
+fn macro_name(definition: &str) -> &str {
+    let end = definition.find([' ', '(']).unwrap_or(definition.len());
+    &definition[..end]
+}
+// A list of macro entries in .debug_macro, from the unit's DW_AT_macros or
+// from a DW_MACRO_import
+struct MacroList<R: Reader> {
+    input: R,
+    format: gimli::Format,
+    // the operand forms of each opcode, so opcodes that aren't known can be
+    // skipped
+    operands: HashMap<u8, Vec<gimli::DwForm>>,
+    // the line program its file indexes are into, if it names one
+    line_offset: Option<gimli::DebugLineOffset>,
+}
+fn read_macro_list<R: Reader>(section: &R, offset: usize) -> gimli::Result<MacroList<R>> {
+    let mut input = section.clone();
+    input.skip(offset)?;
+    let version = input.read_u16()?;
+    if version != 4 && version != 5 {
+        return Err(gimli::Error::UnknownVersion(version.into()));
+    }
+    let flags = input.read_u8()?;
+    let format = if flags & 1 != 0 {
+        gimli::Format::Dwarf64
+    } else {
+        gimli::Format::Dwarf32
+    };
+    let line_offset = match flags & 2 {
+        0 => None,
+        _ => Some(gimli::DebugLineOffset(input.read_offset(format)?)),
+    };
+    let mut operands = HashMap::new();
+    if flags & 4 != 0 {
+        for _ in 0..input.read_u8()? {
+            let opcode = input.read_u8()?;
+            let count = input.read_uleb128()?;
+            let mut forms = Vec::new();
+            for _ in 0..count {
+                forms.push(gimli::DwForm(input.read_u8()?.into()));
+            }
+            operands.insert(opcode, forms);
+        }
+    }
+    Ok(MacroList {
+        input,
+        format,
+        operands,
+        line_offset,
+    })
+}
+// Skip an operand of a vendor opcode
+fn skip_form<R: Reader>(
+    input: &mut R,
+    form: gimli::DwForm,
+    format: gimli::Format,
+) -> gimli::Result<()> {
+    match form {
+        gimli::DW_FORM_flag | gimli::DW_FORM_data1 | gimli::DW_FORM_strx1 => input.skip(1),
+        gimli::DW_FORM_data2 | gimli::DW_FORM_strx2 => input.skip(2),
+        gimli::DW_FORM_strx3 => input.skip(3),
+        gimli::DW_FORM_data4 | gimli::DW_FORM_strx4 => input.skip(4),
+        gimli::DW_FORM_data8 => input.skip(8),
+        gimli::DW_FORM_data16 => input.skip(16),
+        gimli::DW_FORM_udata | gimli::DW_FORM_sdata | gimli::DW_FORM_strx => input.skip_leb128(),
+        gimli::DW_FORM_strp
+        | gimli::DW_FORM_line_strp
+        | gimli::DW_FORM_strp_sup
+        | gimli::DW_FORM_sec_offset => input.read_offset(format).map(|_| ()),
+        gimli::DW_FORM_string => input.read_null_terminated_slice().map(|_| ()),
+        gimli::DW_FORM_block1 => {
+            let len = input.read_u8()?;
+            input.skip(len.into())
+        }
+        gimli::DW_FORM_block2 => {
+            let len = input.read_u16()?;
+            input.skip(len.into())
+        }
+        gimli::DW_FORM_block4 => {
+            let len = input.read_u32()?;
+            input.skip(len as usize)
+        }
+        gimli::DW_FORM_block => {
+            let len = input.read_uleb128()?;
+            input.skip(len as usize)
+        }
+        _otherwise => Err(gimli::Error::UnknownForm),
+    }
+}
+// Make the tag of a #define, macros that aren't in a file, i.e. the
+// predefined ones and those given with -D, are left out
+fn macro_tag<R: Reader>(
+    definition: R,
+    line: u64,
+    file: Option<&Option<String>>,
+    opcode: &str,
+    unit_info: &UnitInfo,
+    options: &Options,
+) -> gimli::Result<Option<FunctionInfo>> {
+    let Some(Some(file)) = file else {
+        return Ok(None);
+    };
+    let definition = definition.to_string()?;
+    let name = macro_name(&definition);
+    if name.is_empty() {
+        return Ok(None);
+    }
+    let mut fields = Vec::new();
+    if options.fields.contains(&"dwarftag") {
+        fields.push(("dwarftag", opcode.to_string()));
+    }
+    Ok(Some(FunctionInfo {
+        func_name: name.to_string(),
+        file_name: file.clone(),
+        line_number: line,
+        column_number: 0,
+        kind: Kind::Macro,
+        scope: None,
+        fields,
+        comp_dir: unit_info.comp_dir.clone(),
+    }))
+}
+// Collect the #defines of a .debug_macro list and the lists it imports. The
+// imports are followed without recursion, at most --max-depth deep.
+#[allow(clippy::too_many_arguments)]
+fn debug_macro_tags<R: Reader>(
+    dwarf: &gimli::Dwarf<R>,
+    unit: &gimli::Unit<R>,
+    unit_info: &UnitInfo,
+    sections: &MacroSections<R>,
+    offset: usize,
+    options: &Options,
+    file_info_list: &mut Vec<FunctionInfo>,
+    stats: &mut Stats,
+) -> Result<(), String> {
+    let section = &sections.debug_macro;
+    let error = |e: gimli::Error| e.to_string();
+    let first = read_macro_list(section, offset).map_err(error)?;
+    // the file indexes are into the line program the list names, normally
+    // the unit's own
+    let unit_line_offset = unit.line_program.as_ref().map(|p| p.header().offset());
+    let files = match first.line_offset {
+        Some(line_offset) if Some(line_offset) != unit_line_offset => {
+            let program = dwarf
+                .debug_line
+                .program(line_offset, unit.encoding().address_size, None, None)
+                .map_err(error)?;
+            Cow::Owned(file_table(dwarf, unit, program.header()))
+        }
+        _ => Cow::Borrowed(&unit_info.files),
+    };
+    // the files the entries are in, DW_MACRO_start_file pushes one and
+    // DW_MACRO_end_file pops it
+    let mut in_files: Vec<Option<String>> = Vec::new();
+    let mut lists = vec![first];
+    while let Some(list) = lists.last_mut() {
+        let input = &mut list.input;
+        let opcode = gimli::DwMacro(input.read_u8().map_err(error)?);
+        let definition = match opcode {
+            // the end of this list, go back to the one that imported it
+            gimli::DwMacro(0) => {
+                lists.pop();
+                None
+            }
+            gimli::DW_MACRO_define => {
+                let line = input.read_uleb128().map_err(error)?;
+                let value = input.read_null_terminated_slice().map_err(error)?;
+                Some((line, value))
+            }
+            gimli::DW_MACRO_define_strp | gimli::DW_MACRO_define_sup => {
+                let line = input.read_uleb128().map_err(error)?;
+                let offset = gimli::DebugStrOffset(input.read_offset(list.format).map_err(error)?);
+                let value = match opcode {
+                    gimli::DW_MACRO_define_strp => gimli::AttributeValue::DebugStrRef(offset),
+                    _ => gimli::AttributeValue::DebugStrRefSup(offset),
+                };
+                Some((line, dwarf.attr_string(unit, value).map_err(error)?))
+            }
+            gimli::DW_MACRO_define_strx => {
+                let line = input.read_uleb128().map_err(error)?;
+                let index =
+                    gimli::DebugStrOffsetsIndex(input.read_uleb128().map_err(error)? as usize);
+                let value = gimli::AttributeValue::DebugStrOffsetsIndex(index);
+                Some((line, dwarf.attr_string(unit, value).map_err(error)?))
+            }
+            gimli::DW_MACRO_undef => {
+                input.skip_leb128().map_err(error)?;
+                input.read_null_terminated_slice().map_err(error)?;
+                None
+            }
+            gimli::DW_MACRO_undef_strp | gimli::DW_MACRO_undef_sup => {
+                input.skip_leb128().map_err(error)?;
+                input.read_offset(list.format).map_err(error)?;
+                None
+            }
+            gimli::DW_MACRO_undef_strx => {
+                input.skip_leb128().map_err(error)?;
+                input.skip_leb128().map_err(error)?;
+                None
+            }
+            gimli::DW_MACRO_start_file => {
+                input.skip_leb128().map_err(error)?;
+                let index = input.read_uleb128().map_err(error)?;
+                in_files.push(files.get(index as usize).cloned().flatten());
+                None
+            }
+            gimli::DW_MACRO_end_file => {
+                in_files.pop();
+                None
+            }
+            gimli::DW_MACRO_import => {
+                let offset = input.read_offset(list.format).map_err(error)?;
+                match (sections.follow_imports, lists.len() > options.max_depth) {
+                    (false, _) => {}
+                    (true, true) => stats.warn(&format!(
+                        "macro imports of the unit at 0x{:x} go deeper than --max-depth {}, leaving the rest out",
+                        unit_start(unit),
+                        options.max_depth
+                    )),
+                    (true, false) => lists.push(read_macro_list(section, offset).map_err(error)?),
+                }
+                None
+            }
+            // a list in the supplementary file, which isn't read
+            gimli::DW_MACRO_import_sup => {
+                input.read_offset(list.format).map_err(error)?;
+                None
+            }
+            gimli::DwMacro(other) => {
+                let forms = list
+                    .operands
+                    .get(&other)
+                    .ok_or_else(|| format!("unknown macro opcode 0x{:x}", other))?;
+                for form in forms.iter() {
+                    skip_form(input, *form, list.format).map_err(error)?;
+                }
+                None
+            }
+        };
+        if let Some((line, value)) = definition {
+            let tag = macro_tag(
+                value,
+                line,
+                in_files.last(),
+                opcode.static_string().unwrap_or_default(),
+                unit_info,
+                options,
+            )
+            .map_err(error)?;
+            file_info_list.extend(tag);
+        }
+    }
+    Ok(())
+}
+// Collect the #defines of a .debug_macinfo list
+fn debug_macinfo_tags<R: Reader>(
+    unit_info: &UnitInfo,
+    section: &R,
+    offset: usize,
+    options: &Options,
+    file_info_list: &mut Vec<FunctionInfo>,
+) -> gimli::Result<()> {
+    let mut input = section.clone();
+    input.skip(offset)?;
+    let mut in_files: Vec<Option<String>> = Vec::new();
+    loop {
+        // the DW_MACINFO_* constants
+        match input.read_u8()? {
+            0 => return Ok(()),
+            // define
+            1 => {
+                let line = input.read_uleb128()?;
+                let value = input.read_null_terminated_slice()?;
+                let tag = macro_tag(
+                    value,
+                    line,
+                    in_files.last(),
+                    "DW_MACINFO_define",
+                    unit_info,
+                    options,
+                )?;
+                file_info_list.extend(tag);
+            }
+            // undef
+            2 => {
+                input.skip_leb128()?;
+                input.read_null_terminated_slice()?;
+            }
+            // start_file
+            3 => {
+                input.skip_leb128()?;
+                let index = input.read_uleb128()?;
+                in_files.push(unit_info.files.get(index as usize).cloned().flatten());
+            }
+            // end_file
+            4 => {
+                in_files.pop();
+            }
+            // vendor_ext
+            0xff => {
+                input.skip_leb128()?;
+                input.read_null_terminated_slice()?;
+            }
+            _otherwise => return Err(gimli::Error::UnknownForm),
+        }
+    }
+}
+// Tag the #defines of a unit built with -g3, from the .debug_macro or
+// .debug_macinfo list its root DIE points to. A list that can't be read to
+// the end keeps the tags read before the damage.
+fn process_macros<R: Reader>(
+    dwarf: &gimli::Dwarf<R>,
+    unit: &gimli::Unit<R>,
+    unit_info: &UnitInfo,
+    sections: &MacroSections<R>,
+    options: &Options,
+    file_info_list: &mut Vec<FunctionInfo>,
+    stats: &mut Stats,
+) {
+    let mut entries = unit.entries();
+    let Ok(Some((_, root))) = entries.next_dfs() else {
+        return;
+    };
+    let attr = |name| root.attr_value(name).ok().flatten();
+    let result = match (
+        attr(gimli::DW_AT_macros).or_else(|| attr(gimli::DW_AT_GNU_macros)),
+        attr(gimli::DW_AT_macro_info),
+    ) {
+        (Some(gimli::AttributeValue::DebugMacroRef(offset)), _) => debug_macro_tags(
+            dwarf,
+            unit,
+            unit_info,
+            sections,
+            offset.0,
+            options,
+            file_info_list,
+            stats,
+        ),
+        // DW_AT_GNU_macros is a plain section offset
+        (Some(gimli::AttributeValue::SecOffset(offset)), _) => debug_macro_tags(
+            dwarf,
+            unit,
+            unit_info,
+            sections,
+            offset,
+            options,
+            file_info_list,
+            stats,
+        ),
+        (_, Some(gimli::AttributeValue::DebugMacinfoRef(offset))) => debug_macinfo_tags(
+            unit_info,
+            &sections.debug_macinfo,
+            offset.0,
+            options,
+            file_info_list,
+        )
+        .map_err(|e| e.to_string()),
+        _otherwise => Ok(()),
+    };
+    if let Err(e) = result {
+        stats.warn(&format!(
+            "could not read the macros of the unit at 0x{:x}: {}, the rest of them are left out",
+            unit_start(unit),
+            e
+        ));
+    }
+}
+// The DW_AT_dwo_name (or DW_AT_GNU_dwo_name before DWARF 5) of a skeleton unit
+fn unit_dwo_name<R: Reader>(dwarf: &gimli::Dwarf<R>, unit: &gimli::Unit<R>) -> Option<String> {
+    let name = unit.dwo_name().ok()??;
+    Some(
+        dwarf
+            .attr_string(unit, name)
+            .ok()?
+            .to_string()
+            .ok()?
+            .to_string(),
+    )
+}
+// Where to look for a .dwo file: in --dwo-dir, both the name as given and
+// just its file name, then relative to the unit's compilation directory
+fn dwo_candidates(
+    dwo_name: &str,
+    comp_dir: Option<&str>,
+    options: &Options,
+) -> Vec<std::path::PathBuf> {
+    let name = std::path::Path::new(dwo_name);
+    let mut candidates = Vec::new();
+    if let Some(dir) = &options.dwo_dir {
+        let dir = std::path::Path::new(dir);
+        if name.is_relative() {
+            candidates.push(dir.join(name));
+        }
+        if let Some(file_name) = name.file_name() {
+            candidates.push(dir.join(file_name));
+        }
+    }
+    match comp_dir {
+        Some(comp_dir) => candidates.push(std::path::Path::new(comp_dir).join(name)),
+        None => candidates.push(name.to_path_buf()),
+    }
+    candidates
+}
+// Load the .dwo file of a skeleton unit and collect the tags of its split unit
+#[allow(clippy::too_many_arguments)]
+fn process_dwo<'a>(
+    dwarf: &gimli::Dwarf<gimli::EndianSlice<'a, gimli::RunTimeEndian>>,
+    skeleton: &gimli::Unit<gimli::EndianSlice<'a, gimli::RunTimeEndian>>,
+    skeleton_info: &UnitInfo,
+    dwo_name: &str,
+    symbols: &Symbols,
+    options: &Options,
+    stats: &mut Stats,
+    file_info_list: &mut Vec<FunctionInfo>,
+    demangled: &mut Demangled,
+) -> Result<std::path::PathBuf, String> {
+    let candidates = dwo_candidates(dwo_name, skeleton_info.comp_dir.as_deref(), options);
+    let (path, data) = candidates
+        .iter()
+        .find_map(|p| fs::read(p).ok().map(|d| (p, d)))
+        .ok_or_else(|| format!("could not find {}", dwo_name))?;
+    let file = ElfBytes::<'_, elf::endian::AnyEndian>::minimal_parse(&data)
+        .map_err(|e| format!("could not parse {}: {}", path.display(), e))?;
+    let endian = match file.ehdr.endianness {
+        elf::endian::AnyEndian::Little => gimli::RunTimeEndian::Little,
+        elf::endian::AnyEndian::Big => gimli::RunTimeEndian::Big,
+    };
+    let dwo_sections = gimli::DwarfSections::load(|id| load_dwo_section(id, &file, stats)).unwrap();
+    let mut dwo = dwo_sections.borrow(|section| gimli::EndianSlice::new(section, endian));
+    dwo.make_dwo(dwarf);
+    let mut other_units = OtherUnits::new(&dwo, options.group_anonymous_by_file);
+    let mut iter = dwo.units();
+    while let Some(header) = iter
+        .next()
+        .map_err(|e| format!("could not read {}: {}", path.display(), e))?
+    {
+        let mut unit = dwo
+            .unit(header)
+            .map_err(|e| format!("could not read {}: {}", path.display(), e))?;
+        unit.copy_relocated_attributes(skeleton);
+        // the split unit's DW_AT_decl_file indexes go into the skeleton's
+        // line program, so its file table is used
+        let unit_info = UnitInfo {
+            language: unit_language(&unit),
+            comp_dir: skeleton_info.comp_dir.clone(),
+            files: skeleton_info.files.clone(),
+        };
+        process_unit(
+            &dwo,
+            &unit,
+            &unit_info,
+            symbols,
+            options,
+            stats,
+            file_info_list,
+            &mut other_units,
+            demangled,
+        );
+    }
+    Ok(path.clone())
+}
+// Function symbols of the elf file, by address
+struct Symbols {
+    by_address: HashMap<u64, String>,
+    // the address the first PT_LOAD segment is linked at, what --load-bias
+    // addresses are relative to. 0 for most shared objects, but prelinked
+    // ones and executables are linked at a fixed base.
+    load_base: u64,
+}
+// The lowest PT_LOAD address, rounded down to the segment alignment
+fn read_load_base(file: &elf::ElfBytes<'_, elf::endian::AnyEndian>) -> u64 {
+    let Some(segments) = file.segments() else {
+        return 0;
+    };
+    segments
+        .iter()
+        .filter(|p| p.p_type == elf::abi::PT_LOAD)
+        .map(|p| p.p_vaddr & !p.p_align.saturating_sub(1))
+        .min()
+        .unwrap_or(0)
+}
+// Read the function symbols from .symtab and .dynsym. The addresses in a
+// relocatable object are all relative to their own section, so they can't be
+// matched up and are left out.
+fn read_symbols(file: &elf::ElfBytes<'_, elf::endian::AnyEndian>) -> Symbols {
+    let mut by_address = HashMap::new();
+    if file.ehdr.e_type == elf::abi::ET_REL {
+        return Symbols {
+            by_address,
+            load_base: 0,
+        };
+    }
+    let tables = [
+        file.symbol_table().ok().flatten(),
+        file.dynamic_symbol_table().ok().flatten(),
+    ];
+    for (symtab, strtab) in tables.into_iter().flatten() {
+        for sym in symtab.iter() {
+            if sym.st_symtype() != elf::abi::STT_FUNC || sym.st_value == 0 {
+                continue;
+            }
+            // the low bit of an arm symbol marks thumb code, it isn't part
+            // of the address
+            let mut address = sym.st_value;
+            if file.ehdr.e_machine == elf::abi::EM_ARM {
+                address &= !1;
+            }
+            if let Ok(name) = strtab.get(sym.st_name as usize) {
+                if name.is_empty() {
+                    continue;
+                }
+                // of several names for one address keep the one that sorts
+                // first, so the choice doesn't depend on the order the
+                // linker wrote the symbols in
+                match by_address.get(&address) {
+                    Some(existing) if existing.as_str() <= name => {}
+                    _ => {
+                        by_address.insert(address, name.to_string());
+                    }
+                }
+            }
+        }
+    }
+    Symbols {
+        by_address,
+        load_base: read_load_base(file),
+    }
+}
+// Default for --max-depth, deeper chains of abstract origins and
+// specifications are cut off
+const DEFAULT_MAX_DEPTH: usize = 64;
+// Where a unit starts in .debug_info or .debug_types, for messages
+fn unit_start<R: Reader>(unit: &gimli::Unit<R>) -> usize {
+    match unit.header.offset() {
+        gimli::UnitSectionOffset::DebugInfoOffset(o) => o.0,
+        gimli::UnitSectionOffset::DebugTypesOffset(o) => o.0,
+    }
+}
+// The offset of a DIE in .debug_info, for messages
+fn die_offset<R: Reader>(
+    unit: &gimli::Unit<R>,
+    entry: &gimli::DebuggingInformationEntry<R>,
+) -> usize {
+    match entry.offset().to_unit_section_offset(unit) {
+        gimli::UnitSectionOffset::DebugInfoOffset(o) => o.0,
+        gimli::UnitSectionOffset::DebugTypesOffset(o) => o.0,
+    }
+}
+#[allow(clippy::too_many_arguments)]
+fn process_subprogram<R: Reader>(
+    dwarf: &gimli::Dwarf<R>,
+    unit: &gimli::Unit<R>,
+    unit_info: &UnitInfo,
+    entry: &gimli::DebuggingInformationEntry<R>,
+    symbols: &Symbols,
+    options: &Options,
+    other_units: &mut OtherUnits<R>,
+    demangled: &mut Demangled,
+    stats: &mut Stats,
+) -> Option<FunctionInfo> {
+    let mut decl = read_decl_attrs(dwarf, unit, unit_info, entry, stats);
+    // --only-with-code: declarations, abstract instances of inline functions
+    // and functions optimised away have no address of their own
+    if options.only_with_code
+        && decl.low_pc.is_none()
+        && !matches!(entry.attr_value(gimli::DW_AT_ranges), Ok(Some(_)))
+    {
+        return None;
+    }
+    // Instances and out of line definitions often leave out the name and decl
+    // coordinates, fill the missing ones in from the DIE they refer to. This
+    // is a loop rather than recursion and is bounded, so a very long or
+    // circular chain of references can't overflow the stack or hang.
+    let mut next = origin_target(dwarf, other_units, None, &decl);
+    // the last DIE reached in another unit, which gives the scope
+    let mut declared_in = None;
+    // the last DIE reached, the declaration when there is one. Instances
+    // can leave their parameters out, it always lists them.
+    let mut declaration = None;
+    let mut depth = 0;
+    while let Some((kind, in_unit, offset)) = next {
+        depth += 1;
+        if depth > options.max_depth {
+            stats.warn(&format!(
+                "references from the DIE at 0x{:x} go deeper than --max-depth {}, using what was found so far",
+                die_offset(unit, entry),
+                options.max_depth
+            ));
+            break;
+        }
+        let (origin_unit, origin_info) = match in_unit {
+            Some(i) => (&other_units.loaded[i].unit, &other_units.loaded[i].info),
+            None => (unit, unit_info),
+        };
+        let origin_entry = origin_unit.entry(offset).ok()?;
+        let origin_dwarf = other_units.dwarf(dwarf, in_unit);
+        let origin = read_decl_attrs(origin_dwarf, origin_unit, origin_info, &origin_entry, stats);
+        declared_in = in_unit.map(|i| (i, offset)).or(declared_in);
+        declaration = Some((in_unit, offset));
+        next = origin_target(dwarf, other_units, in_unit, &origin);
+        decl.func_name = decl.func_name.or(origin.func_name);
+        decl.linkage_name = decl.linkage_name.or(origin.linkage_name);
+        decl.visibility = decl.visibility.or(origin.visibility);
+        decl.prototyped |= origin.prototyped;
+        decl.ref_qualifier = decl.ref_qualifier.or(origin.ref_qualifier);
+        let origin_has_location = origin.file_name.is_some() && origin.line_number.is_some();
+        if options.follow_origin_for_location
+            && kind == OriginKind::AbstractOrigin
+            && origin_has_location
+        {
+            // prefer where the abstract origin says the source lives
+            decl.file_name = origin.file_name;
+            decl.line_number = origin.line_number;
+            decl.column_number = origin.column_number;
+        } else {
+            decl.file_name = decl.file_name.or(origin.file_name);
+            decl.line_number = decl.line_number.or(origin.line_number);
+            decl.column_number = decl.column_number.or(origin.column_number);
+        }
+    }
+    let mut fields = Vec::new();
+    // the entry address, as linked. Only looked for when it's needed as the
+    // ranges have to be read for functions split into several parts.
+    let address = if decl.func_name.is_none() || options.fields.contains(&"address") {
+        decl.low_pc.or_else(|| {
+            let mut ranges = dwarf.die_ranges(unit, entry).ok()?;
+            Some(ranges.next().ok()??.begin)
+        })
+    } else {
+        None
+    };
+    if decl.func_name.is_none() {
+        // Optimised code can leave a function without a name, look its
+        // address up in the symbol table. It has to be placed somewhere, use
+        // the unit's file and line 0 when that is all there is.
+        if let Some(name) = address.and_then(|a| symbols.by_address.get(&a)) {
+            decl.func_name = Some(name.clone());
+            decl.file_name = decl
+                .file_name
+                .or_else(|| Some(unit.name.clone()?.to_string().ok()?.to_string()));
+            decl.line_number = decl.line_number.or(Some(0));
+            fields.push(("namesrc", "symtab".to_string()));
+        }
+    }
+    // only written when the compiler recorded it
+    let visibility = match decl.visibility {
+        Some(gimli::DW_VIS_local) => Some("local"),
+        Some(gimli::DW_VIS_exported) => Some("exported"),
+        Some(gimli::DW_VIS_qualified) => Some("qualified"),
+        _ => None,
+    };
+    if let Some(visibility) = visibility {
+        fields.push(("visibility", visibility.to_string()));
+    }
+    let trait_impl = match (&decl.linkage_name, unit_info.language) {
+        (Some(name), Some(gimli::DW_LANG_Rust)) if options.fields.contains(&"impl") => {
+            rust_trait_impl(name, demangled)
+        }
+        _ => None,
+    };
+    if let Some(trait_impl) = trait_impl {
+        fields.push(("impl", trait_impl));
+    }
+    let shim = match (&decl.linkage_name, unit_info.language) {
+        (Some(name), Some(gimli::DW_LANG_Rust)) => rust_shim(name, demangled),
+        _ => None,
+    };
+    if let Some(shim) = shim {
+        match options.rust_closures {
+            RustClosures::Skip => return None,
+            RustClosures::Keep => {}
+            RustClosures::Qualify => {
+                let separator = scope_separator(unit_info.language, options);
+                decl.func_name = decl
+                    .func_name
+                    .map(|f| format!("{}{}{}", f, separator, shim));
+            }
+        }
+    }
+    let signature = match (options.fields.contains(&"signature"), declaration) {
+        (false, _) => None,
+        (true, Some((in_unit, offset))) => signature(
+            other_units.dwarf(dwarf, in_unit),
+            in_unit.map_or(unit, |i| &other_units.loaded[i].unit),
+            unit_info.language,
+            offset,
+            decl.ref_qualifier,
+            options.max_depth,
+        ),
+        (true, None) => signature(
+            dwarf,
+            unit,
+            unit_info.language,
+            entry.offset(),
+            decl.ref_qualifier,
+            options.max_depth,
+        ),
+    };
+    if let Some(signature) = signature {
+        fields.push(("signature", signature));
+    }
+    // only C has functions without a prototype
+    if is_c(unit_info.language) && options.fields.contains(&"prototyped") {
+        fields.push(("prototyped", decl.prototyped.to_string()));
+    }
+    if let (true, Some(address)) = (options.fields.contains(&"address"), address) {
+        // --load-bias moves the load base to where it is at run time
+        let address = match options.load_bias {
+            Some(bias) => address.wrapping_sub(symbols.load_base).wrapping_add(bias),
+            None => address,
+        };
+        fields.push(("address", format!("0x{:x}", address)));
+    }
+    let col = decl.column_number.unwrap_or(0);
+    if let (Some(func), Some(file), Some(line)) = (decl.func_name, decl.file_name, decl.line_number)
+    {
+        // function has all the required fields
+        let separator = scope_separator(unit_info.language, options);
+        if is_go(unit_info.language) {
+            let (func, scope) = split_go_name(&func, separator);
+            return Some(FunctionInfo {
+                func_name: func,
+                file_name: file,
+                line_number: line,
+                column_number: col,
+                kind: Kind::Function,
+                scope,
+                fields,
+                comp_dir: unit_info.comp_dir.clone(),
+            });
+        }
+        if is_ada(unit_info.language) {
+            let (func, package) = split_ada_name(&func, options.ada_names, separator);
+            return Some(FunctionInfo {
+                func_name: func,
+                file_name: file,
+                line_number: line,
+                column_number: col,
+                kind: Kind::Subprogram,
+                scope: package.map(|p| (Kind::Package, p)),
+                fields,
+                comp_dir: unit_info.comp_dir.clone(),
+            });
+        }
+        return Some(FunctionInfo {
+            func_name: func,
+            file_name: file,
+            line_number: line,
+            column_number: col,
+            kind: Kind::Function,
+            scope: other_units.scope(declared_in, separator),
+            fields,
+            comp_dir: unit_info.comp_dir.clone(),
+        });
+    }
+    None
+}
+// Every extension field a tag can have besides the kind and scope, and
+// whether it is written by default
+const ALL_FIELDS: [(&str, bool); 9] = [
+    // namesrc:symtab, the name came from the symbol table
+    ("namesrc", true),
+    ("visibility", true),
+    ("typeref", true),
+    ("address", false),
+    // the DW_TAG_* the tag was made from, to see why it got its kind
+    ("dwarftag", false),
+    // prototyped:true or false, for the functions of C units
+    ("prototyped", false),
+    // the comment above the tag in its source file
+    ("doc", false),
+    // impl:Trait for Type, the trait impl of a Rust method
+    ("impl", false),
+    // signature:(int, const char *) const, the parameter types and qualifiers
+    // of a function
+    ("signature", false),
+];
+// The letter written for a kind, after any --kind-map overrides
+fn kind_letter(kind: Kind, options: &Options) -> char {
+    *options.kind_map.get(&kind).unwrap_or(&kind.letter())
+}
+// How each tag finds its line
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ExCmd {
+    // :<line>
+    Number,
+    // /^<source line>$/, falls back to the line number without the source
+    Pattern,
+}
+// What --tabs-to-spaces does with tabs inside a pattern
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum TabReplace {
+    // write \t, which still matches the tab
+    Escape,
+    // write this many spaces, the pattern no longer matches the source exactly
+    Spaces(usize),
+}
+// What is done with the closures and shims of Rust units, for --rust-closures
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum RustClosures {
+    // leave them out
+    Skip,
+    // tag them by their DWARF names, "{closure#0}"
+    Keep,
+    // name them after the function they are in, "run::{closure#0}", and
+    // shims after what they wrap, "call_once::{shim:vtable#0}"
+    Qualify,
+}
+// How a tag whose line is 0, i.e. unknown, is written, for --line-zero
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum LineZero {
+    // :0, which some editors take as the first line
+    Keep,
+    // :1
+    AsOne,
+    // no line, search for the name from the top of the file instead
+    Omit,
+}
+// How tag names are ordered, for --collation
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Collation {
+    // by their UTF-8 bytes, which is what editors binary search with
+    Byte,
+    // letters before case and accents, see collation_key
+    Fold,
+}
+// The lines of each source file read for patterns, None if it couldn't be read
+#[derive(Default)]
+struct SourceCache {
+    files: HashMap<std::path::PathBuf, Option<Vec<String>>>,
+    // the file names of the tags whose source couldn't be read, for
+    // --fail-on-missing-source
+    missing: BTreeSet<String>,
+}
+impl SourceCache {
+    // The source file of a tag that could be read, relative file names are
+    // looked for in the unit's compilation directory first
+    fn path(&mut self, func_info: &FunctionInfo) -> Option<std::path::PathBuf> {
+        let file = std::path::Path::new(&func_info.file_name);
+        let mut candidates = Vec::new();
+        if let (false, Some(comp_dir)) = (file.is_absolute(), &func_info.comp_dir) {
+            candidates.push(std::path::Path::new(comp_dir).join(file));
+        }
+        candidates.push(file.to_path_buf());
+        let path = candidates.into_iter().find(|p| self.read(p).is_some());
+        if path.is_none() {
+            self.missing.insert(func_info.file_name.clone());
+        }
+        path
+    }
+    // Get a source line
+    fn line(&mut self, func_info: &FunctionInfo) -> Option<&str> {
+        let path = self.path(func_info)?;
+        let lines = self.files.get(&path)?.as_ref()?;
+        let index = (func_info.line_number as usize).checked_sub(1)?;
+        lines.get(index).map(|l| l.as_str())
+    }
+    fn read(&mut self, path: &std::path::Path) -> Option<&Vec<String>> {
+        self.files
+            .entry(path.to_path_buf())
+            .or_insert_with(|| {
+                let data = fs::read(path).ok()?;
+                let text = String::from_utf8_lossy(&data);
+                Some(text.lines().map(|l| l.to_string()).collect())
+            })
+            .as_ref()
+    }
+    // The comment above a tag, for --extract-docs
+    fn doc(&mut self, func_info: &FunctionInfo) -> Option<String> {
+        let path = self.path(func_info)?;
+        let lines = self.files.get(&path)?.as_ref()?;
+        let index = (func_info.line_number as usize).checked_sub(1)?;
+        doc_comment(&func_info.file_name, lines, index)
+    }
+    // Drop what was read of a file, so it is read again
+    fn forget(&mut self, path: &std::path::Path) {
+        self.files.remove(path);
+    }
+}
+// The longest doc: field written, longer comments are cut off with ...
+const DOC_MAX_LEN: usize = 200;
+// How many source lines above a tag are looked at for its comment
+const DOC_MAX_LINES: usize = 64;
+// The comment right above line index of a source file, its lines joined
+// with spaces. Ada and Fortran only have line comments, the C family also
+// has /* */ blocks, and /// and /** are read the same as // and /*. There
+// can't be a blank line between the comment and the tag.
+fn doc_comment(file_name: &str, lines: &[String], index: usize) -> Option<String> {
+    let extension = std::path::Path::new(file_name)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    let line_comment = match extension.as_str() {
+        "adb" | "ads" | "ada" => "--",
+        "f" | "for" | "f90" | "f95" | "f03" | "f08" => "!",
+        _ => "//",
+    };
+    let above = lines.get(index.saturating_sub(DOC_MAX_LINES)..index)?;
+    let mut text: Vec<String> = Vec::new();
+    let last = above.last()?.trim();
+    if line_comment == "//" && last.ends_with("*/") {
+        let start = above.iter().rposition(|l| l.contains("/*"))?;
+        for line in above[start..].iter() {
+            let line = line.trim();
+            let line = line.split_once("/*").map_or(line, |(_, rest)| rest);
+            let line = line.rsplit_once("*/").map_or(line, |(rest, _)| rest);
+            let line = line.trim_start_matches('*').trim_start_matches('!');
+            text.push(line.trim().to_string());
+        }
+    } else {
+        for line in above.iter().rev() {
+            let Some(comment) = line.trim().strip_prefix(line_comment) else {
+                break;
+            };
+            let comment = comment.trim_start_matches(['/', '!', '-']);
+            text.push(comment.trim().to_string());
+        }
+        text.reverse();
+    }
+    let doc = text
+        .iter()
+        .flat_map(|l| l.split_whitespace())
+        .collect::<Vec<&str>>()
+        .join(" ");
+    if doc.is_empty() {
+        return None;
+    }
+    // tabs and newlines are gone already, \ would start an escape
+    let doc = doc.replace('\\', "\\\\");
+    match doc.char_indices().nth(DOC_MAX_LEN) {
+        Some((cut, _)) => Some(format!("{}...", &doc[..cut])),
+        None => Some(doc),
+    }
+}
+// Escape a source line for a /^...$/ search pattern
+fn search_pattern(line: &str, tabs: Option<TabReplace>) -> String {
+    format!("/^{}$/", escape_pattern(line, tabs))
+}
+// Escape text for a search pattern, tags are searched with 'nomagic' so only
+// \ and / need it
+fn escape_pattern(text: &str, tabs: Option<TabReplace>) -> String {
+    let mut pattern = String::new();
+    for c in text.chars() {
+        match c {
+            '\\' => pattern.push_str("\\\\"),
+            '/' => pattern.push_str("\\/"),
+            '\t' => match tabs {
+                Some(TabReplace::Escape) => pattern.push_str("\\t"),
+                Some(TabReplace::Spaces(n)) => pattern.push_str(&" ".repeat(n)),
+                None => pattern.push('\t'),
+            },
+            _ => pattern.push(c),
+        }
+    }
+    pattern
+}
+// The ex command of a tag without a source line to search for
+fn line_command(func_info: &FunctionInfo, options: &Options) -> String {
+    match (func_info.line_number, options.line_zero) {
+        (0, LineZero::AsOne) => ":1".to_string(),
+        // the first mention of the name, vim searches from the top of the
+        // file and stays there if the name isn't found
+        (0, LineZero::Omit) => format!(
+            "/{}/",
+            escape_pattern(&func_info.func_name, options.tabs_to_spaces)
+        ),
+        (line, _) => format!(":{}", line),
+    }
+}
+// format for vim
+fn format_file_info(
+    func_info: &FunctionInfo,
+    sources: &mut SourceCache,
+    options: &Options,
+) -> String {
+    //let col = func_info.column_number;
+    let file = &func_info.file_name;
+    let func = &func_info.func_name;
+    let pattern = match options.excmd {
+        ExCmd::Pattern => sources
+            .line(func_info)
+            .map(|l| search_pattern(l, options.tabs_to_spaces)),
+        ExCmd::Number => None,
+    };
+    let ex_cmd = pattern.unwrap_or_else(|| line_command(func_info, options));
+    let mut tag = format!(
+        "{}\t{}\t{};\"\t{}",
+        func,
+        file,
+        ex_cmd,
+        kind_letter(func_info.kind, options)
+    );
+    if let Some((kind, scope)) = &func_info.scope {
+        tag.push_str(&format!("\t{}:{}", kind.long_name(), scope));
+    }
+    for (name, value) in func_info.fields.iter() {
+        tag.push_str(&format!("\t{}:{}", name, value));
+    }
+    // read from the sources as the tags are written, so it isn't cached
+    if options.fields.contains(&"doc") {
+        if let Some(doc) = sources.doc(func_info) {
+            tag.push_str(&format!("\tdoc:{}", doc));
+        }
+    }
+    tag
+}
+// the ctags header
+const TAG_HEADER: [&str; 2] = [
+    "!_TAG_FILE_FORMAT\t2\t/extended format; --format=1 will not append ;\" to lines/",
+    "!_TAG_FILE_SORTED\t1\t/0=unsorted, 1=sorted, 2=foldcase/",
+];
+const TAG_FILE_UNSORTED: &str = "!_TAG_FILE_SORTED\t0\t/0=unsorted, 1=sorted, 2=foldcase/";
+// The group of a source file for --group-by-directory: its first depth
+// directories joined by _, None for files with fewer directories than that
+// or outside the tree
+fn directory_group(file_name: &str, depth: usize) -> Option<String> {
+    let path = normalize_path(file_name);
+    let mut dirs: Vec<&str> = path.split('/').filter(|p| !p.is_empty()).collect();
+    // the last part is the file itself
+    dirs.pop();
+    // ../x would be written to ...tags
+    if dirs.len() < depth || depth == 0 || dirs.first() == Some(&"..") {
+        return None;
+    }
+    Some(dirs[..depth].join("_"))
+}
+// Write one tags file per source directory into out_dir, <group>.tags, plus
+// _root.tags for the files that aren't in a deep enough directory. Each file
+// has its own header and is sorted.
+fn write_directory_groups(
+    out_dir: &str,
+    file_info_list: &[FunctionInfo],
+    header: &[String],
+    options: &Options,
+    sources: &mut SourceCache,
+) -> std::io::Result<()> {
+    fs::create_dir_all(out_dir)?;
+    let mut groups: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for f in file_info_list.iter() {
+        let group = directory_group(&f.file_name, options.group_depth)
+            .unwrap_or_else(|| "_root".to_string());
+        groups
+            .entry(group)
+            .or_default()
+            .push(format_file_info(f, sources, options));
+    }
+    for (group, lines) in groups {
+        let path = std::path::Path::new(out_dir).join(format!("{}.tags", group));
+        let mut out = std::io::BufWriter::new(fs::File::create(path)?);
+        if options.output_bom {
+            out.write_all(UTF8_BOM)?;
+        }
+        write_tags(&mut out, header, &lines)?;
+    }
+    Ok(())
+}
+// What the output looks like
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Format {
+    // a ctags file
+    Ctags,
+    // the tags of each source file, in line order
+    Outline,
+    // compare: one line per changed symbol
+    Text,
+    // compare: a JSON object
+    Json,
+}
+// Write the tags grouped by source file, for file outline views. Files are
+// sorted by name, and the tags of a file by line, column and then name.
+fn write_outline(
+    out: &mut impl Write,
+    file_info_list: &[FunctionInfo],
+    options: &Options,
+) -> std::io::Result<()> {
+    let mut by_file: BTreeMap<&str, Vec<&FunctionInfo>> = BTreeMap::new();
+    for f in file_info_list.iter() {
+        by_file.entry(f.file_name.as_str()).or_default().push(f);
+    }
+    for (file, mut tags) in by_file {
+        tags.sort_by(|a, b| {
+            (a.line_number, a.column_number, &a.func_name).cmp(&(
+                b.line_number,
+                b.column_number,
+                &b.func_name,
+            ))
+        });
+        writeln!(out, "{}", file)?;
+        for f in tags {
+            let line = match (f.line_number, options.line_zero) {
+                (0, LineZero::AsOne) => "1".to_string(),
+                (0, LineZero::Omit) => "-".to_string(),
+                (line, _) => line.to_string(),
+            };
+            write!(
+                out,
+                "\t{}\t{}\t{}",
+                line,
+                kind_letter(f.kind, options),
+                f.func_name
+            )?;
+            if let Some((kind, scope)) = &f.scope {
+                write!(out, "\t{}:{}", kind.long_name(), scope)?;
+            }
+            writeln!(out)?;
+        }
+    }
+    out.flush()
+}
+// Where the tags came from, written as pseudo tags by --emit-provenance
+struct Provenance {
+    input_file: String,
+    build_id: Option<String>,
+    comp_dir: Option<String>,
+}
+// Get the GNU build-id note of the elf file as hex
+fn read_build_id(file: &elf::ElfBytes<'_, elf::endian::AnyEndian>) -> Option<String> {
+    let shdr = file.section_header_by_name(".note.gnu.build-id").ok()??;
+    let notes = file.section_data_as_notes(&shdr).ok()?;
+    for note in notes {
+        if let elf::note::Note::GnuBuildId(id) = note {
+            return Some(id.0.iter().map(|b| format!("{:02x}", b)).collect());
+        }
+    }
+    None
+}
+// The file named by .debug_sup (DWARF 5) or .gnu_debugaltlink (written by
+// dwz -m), and the build-id the latter expects it to have. None for a file
+// that doesn't refer to one, or is itself a supplementary file.
+fn sup_link(
+    file: &elf::ElfBytes<'_, elf::endian::AnyEndian>,
+    stats: &mut Stats,
+) -> Option<(String, Option<String>)> {
+    let until_nul = |data: &[u8]| -> Option<(String, usize)> {
+        let end = data.iter().position(|b| *b == 0)?;
+        Some((String::from_utf8_lossy(&data[..end]).into_owned(), end + 1))
+    };
+    if let Some(shdr) = file.section_header_by_name(".debug_sup").ok()? {
+        let data = section_bytes(file, ".debug_sup", &shdr, stats);
+        // a version (2 bytes) and is_supplementary (1 byte) before the name
+        if data.get(2) != Some(&0) {
+            return None;
+        }
+        let (name, _) = until_nul(data.get(3..)?)?;
+        return Some((name, None));
+    }
+    let shdr = file.section_header_by_name(".gnu_debugaltlink").ok()??;
+    let data = section_bytes(file, ".gnu_debugaltlink", &shdr, stats);
+    let (name, end) = until_nul(&data)?;
+    let build_id: String = data[end..].iter().map(|b| format!("{:02x}", b)).collect();
+    Some((name, Some(build_id).filter(|id| !id.is_empty())))
+}
+// Read the supplementary file holding the debug info a file shares with
+// others: --sup-file, or the one the file names, a relative name being
+// relative to the file's directory. Also returns the build-id it should have.
+fn read_sup_file(
+    path: &str,
+    file: &elf::ElfBytes<'_, elf::endian::AnyEndian>,
+    options: &Options,
+    stats: &mut Stats,
+) -> Option<(std::path::PathBuf, Vec<u8>, Option<String>)> {
+    let (sup_path, build_id) = match &options.sup_file {
+        Some(sup_file) => (std::path::PathBuf::from(sup_file), None),
+        None => {
+            let (name, build_id) = sup_link(file, stats)?;
+            let dir = std::path::Path::new(path)
+                .parent()
+                .unwrap_or(std::path::Path::new(""));
+            (dir.join(name), build_id)
+        }
+    };
+    match fs::read(&sup_path) {
+        Ok(data) => Some((sup_path, data, build_id)),
+        Err(e) => {
+            stats.warn(&format!(
+                "could not read the supplementary file {}: {}, what refers into it is left out, try --sup-file",
+                sup_path.display(),
+                e
+            ));
+            None
+        }
+    }
+}
+// The header lines to write before the tags
+fn pseudo_tags(options: &Options, provenance: &Provenance) -> Vec<String> {
+    if options.no_header {
+        return Vec::new();
+    }
+    let mut header: Vec<String> = TAG_HEADER.iter().map(|l| l.to_string()).collect();
+    // sorted by collation isn't what editors take as sorted
+    if options.unsorted || options.collation == Collation::Fold {
+        header[1] = TAG_FILE_UNSORTED.to_string();
+    }
+    if options.emit_provenance && !options.no_pseudo_tags {
+        if let Some(build_id) = &provenance.build_id {
+            header.push(format!(
+                "!_TAG_BUILD_ID\t{}\t/build-id of the input file/",
+                build_id
+            ));
+        }
+        if let Some(comp_dir) = &provenance.comp_dir {
+            header.push(format!(
+                "!_TAG_COMP_DIR\t{}\t/compilation directory/",
+                comp_dir
+            ));
+        }
+        header.push(format!(
+            "!_TAG_INPUT_FILE\t{}\t/file the tags were generated from/",
+            provenance.input_file
+        ));
+    }
+    header
+}
+// The (name, file, line) of the last N tags written by --unsorted, so
+// repeats within that window can be left out. The oldest key is dropped
+// once the window is full, so memory stays bounded.
+struct DedupWindow {
+    capacity: usize,
+    order: VecDeque<(String, String, u64)>,
+    keys: HashSet<(String, String, u64)>,
+}
+impl DedupWindow {
+    fn new(capacity: usize) -> DedupWindow {
+        DedupWindow {
+            capacity,
+            order: VecDeque::with_capacity(capacity),
+            keys: HashSet::with_capacity(capacity),
+        }
+    }
+    // true if the tag should be written, i.e. it isn't in the window
+    fn insert(&mut self, f: &FunctionInfo) -> bool {
+        if self.capacity == 0 {
+            return true;
+        }
+        let key = (f.func_name.clone(), f.file_name.clone(), f.line_number);
+        if self.keys.contains(&key) {
+            return false;
+        }
+        if self.order.len() == self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.keys.remove(&oldest);
+            }
+        }
+        self.keys.insert(key.clone());
+        self.order.push_back(key);
+        true
+    }
+}
+// Writes the tags of each unit as soon as it has been walked, for --unsorted.
+// The header goes out with the first tags, once the first unit has filled
+// in the provenance.
+struct TagStream<W: Write> {
+    out: W,
+    sources: SourceCache,
+    window: DedupWindow,
+    header_written: bool,
+    // tags written, repeats left out by the window aren't counted
+    tags: usize,
+}
+impl<W: Write> TagStream<W> {
+    fn new(out: W, options: &Options) -> TagStream<W> {
+        TagStream {
+            out,
+            sources: SourceCache::default(),
+            window: DedupWindow::new(options.dedup_window),
+            header_written: false,
+            tags: 0,
+        }
+    }
+    fn write_header(&mut self, header: &[String]) -> std::io::Result<()> {
+        if !self.header_written {
+            self.header_written = true;
+            for line in header.iter() {
+                writeln!(self.out, "{}", line)?;
+            }
+        }
+        Ok(())
+    }
+    fn write(
+        &mut self,
+        header: &[String],
+        list: &[FunctionInfo],
+        options: &Options,
+    ) -> std::io::Result<()> {
+        self.write_header(header)?;
+        for f in list.iter() {
+            if self.window.insert(f) {
+                writeln!(
+                    self.out,
+                    "{}",
+                    format_file_info(f, &mut self.sources, options)
+                )?;
+                self.tags += 1;
+            }
+        }
+        Ok(())
+    }
+    fn finish(&mut self, header: &[String]) -> std::io::Result<()> {
+        self.write_header(header)?;
+        self.out.flush()
+    }
+}
+// Written before the header with --output-bom, for editors that only take a
+// tags file as UTF-8 when it starts with one
+const UTF8_BOM: &[u8] = b"\xef\xbb\xbf";
+fn write_tags(out: &mut impl Write, header: &[String], lines: &[String]) -> std::io::Result<()> {
+    for line in header.iter() {
+        writeln!(out, "{}", line)?;
+    }
+    for line in lines.iter() {
+        writeln!(out, "{}", line)?;
+    }
+    out.flush()
+}
+// Combine the lines of an existing tags file with freshly generated tags.
+// Every old entry for a source file that was tagged again is dropped, the
+// rest are kept as they are. The result is sorted by tag name without the
+// header lines.
+fn merge_tag_lines(
+    existing: &str,
+    new_lines: &[String],
+    new_files: &[&str],
+    collation: Collation,
+) -> Vec<String> {
+    let mut lines: Vec<String> = existing
+        .lines()
+        .filter(|line| !line.starts_with("!_TAG_") && !line.is_empty())
+        .filter(|line| match line.split('\t').nth(1) {
+            Some(file) => !new_files.contains(&file),
+            None => true,
+        })
+        .map(|line| line.to_string())
+        .collect();
+    lines.extend(new_lines.iter().cloned());
+    sort_tag_lines(&mut lines, collation);
+    lines
+}
+// Sort tag lines by tag name and drop repeated ones, returning the repeats
+fn sort_tag_lines(lines: &mut Vec<String>, collation: Collation) -> Vec<String> {
+    let tag_name = |line: &String| line.split('\t').next().unwrap_or("").to_string();
+    lines.sort_by(|a, b| tag_name(a).cmp(&tag_name(b)).then(a.cmp(b)));
+    if collation == Collation::Fold {
+        // stable, so repeated lines stay next to each other
+        lines.sort_by_cached_key(|line| collation_key(&tag_name(line)));
+    }
+    let mut kept: Vec<String> = Vec::with_capacity(lines.len());
+    let mut removed = Vec::new();
+    for line in lines.drain(..) {
+        if kept.last() == Some(&line) {
+            removed.push(line);
+        } else {
+            kept.push(line);
+        }
+    }
+    *lines = kept;
+    removed
+}
+// Latin letters with diacritics and ligatures, lower case, and the letters
+// they sort with
+const LATIN_BASE_LETTERS: [(&str, &str); 28] = [
+    ("àáâãäåāăą", "a"),
+    ("æ", "ae"),
+    ("çćĉċč", "c"),
+    ("ðďđ", "d"),
+    ("èéêëēĕėęě", "e"),
+    ("ĝğġģ", "g"),
+    ("ĥħ", "h"),
+    ("ìíîïĩīĭįı", "i"),
+    ("ĳ", "ij"),
+    ("ĵ", "j"),
+    ("ķĸ", "k"),
+    ("ĺļľŀł", "l"),
+    ("ñńņňŉŋ", "n"),
+    ("òóôõöøōŏő", "o"),
+    ("œ", "oe"),
+    ("ŕŗř", "r"),
+    ("śŝşšſ", "s"),
+    ("ß", "ss"),
+    ("ţťŧ", "t"),
+    ("þ", "th"),
+    ("ùúûüũūŭůűų", "u"),
+    ("ŵ", "w"),
+    ("ýÿŷ", "y"),
+    ("źżž", "z"),
+    ("ǎ", "a"),
+    ("ǐ", "i"),
+    ("ǒ", "o"),
+    ("ǔǖǘǚǜ", "u"),
+];
+// The levels of a collation key: (group, letter), accent, upper case, bytes
+type CollationKey = (Vec<(u8, u32)>, Vec<u32>, Vec<u8>, String);
+// The sort key of a name for --collation fold: names are compared by their
+// letters first, ignoring case and the accents of the Latin letters in
+// LATIN_BASE_LETTERS, then by those accents, then by case (lower case
+// first), and last by their bytes so only equal names are equal. At the
+// first level punctuation sorts before digits and digits before letters,
+// everything else is in code point order. This is no Unicode collation,
+// just enough folding for names written in Latin letters.
+fn collation_key(name: &str) -> CollationKey {
+    let mut letters = Vec::new();
+    let mut accents = Vec::new();
+    let mut case = Vec::new();
+    for c in name.chars() {
+        let lower = c.to_lowercase().next().unwrap_or(c);
+        let base = match lower.is_ascii() {
+            true => None,
+            false => LATIN_BASE_LETTERS
+                .iter()
+                .find(|(accented, _)| accented.contains(lower))
+                .map(|(_, base)| *base),
+        };
+        let accent = if base.is_some() { lower as u32 } else { 0 };
+        let upper = u8::from(c != lower);
+        let mut push = |b: char| {
+            let group = if b.is_alphabetic() {
+                2
+            } else if b.is_numeric() {
+                1
+            } else {
+                0
+            };
+            letters.push((group, b as u32));
+            accents.push(accent);
+            case.push(upper);
+        };
+        match base {
+            Some(base) => base.chars().for_each(&mut push),
+            None => push(lower),
+        }
+    }
+    (letters, accents, case, name.to_string())
+}
+// 64 bit FNV-1a, a quick hash that is the same on every platform
+fn fnv1a(data: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for b in data.iter() {
+        hash ^= u64::from(*b);
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+// Bumped whenever the layout of a cache file changes
+const CACHE_VERSION: u32 = 1;
+// A file's entry in --cache-dir is named after the hash of its contents and
+// of the options that change what is read out of it, so any change to either
+// finds a different entry. The file's name and modification time don't
+// matter.
+fn cache_path(dir: &str, file_data: &[u8], options: &Options) -> std::path::PathBuf {
+    let reading_options = format!(
+        "{} {} {} {} {} {} {:?} {:?} {:?} {:?} {:?} {:?} {:?} {:?}",
+        env!("CARGO_PKG_VERSION"),
+        options.ada_names,
+        options.follow_origin_for_location,
+        options.group_anonymous_by_file,
+        options.only_with_code,
+        options.max_depth,
+        options.scope_separator,
+        options.kinds,
+        options.kinds_by_language,
+        options.fields,
+        options.load_bias,
+        options.dwo_dir,
+        options.sup_file,
+        options.rust_closures
+    );
+    std::path::Path::new(dir).join(format!(
+        "{:016x}-{:016x}.cache",
+        fnv1a(file_data),
+        fnv1a(reading_options.as_bytes())
+    ))
+}
+// Cache files are text, one record per line with tab separated values
+fn cache_escape(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+}
+fn cache_unescape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => out.push('\t'),
+            Some('n') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => {}
+        }
+    }
+    out
+}
+// "-" for None, "=value" otherwise
+fn cache_option(value: Option<&str>) -> String {
+    match value {
+        Some(v) => format!("={}", cache_escape(v)),
+        None => "-".to_string(),
+    }
+}
+fn cache_parse_option(value: &str) -> Option<Option<String>> {
+    match value {
+        "-" => Some(None),
+        _ => Some(Some(cache_unescape(value.strip_prefix('=')?))),
+    }
+}
+fn write_cache(
+    path: &std::path::Path,
+    list: &[FunctionInfo],
+    provenance: &Provenance,
+    stats: &Stats,
+    dwo_files: &[std::path::PathBuf],
+) -> std::io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut lines = vec![format!("dwarf-easy-cache\t{}", CACHE_VERSION)];
+    lines.push(format!(
+        "stats\t{}\t{}\t{}",
+        stats.units, stats.subprograms, stats.relocatable as u8
+    ));
+    for (version, count) in stats.unit_versions.iter() {
+        lines.push(format!("version\t{}\t{}", version, count));
+    }
+    lines.push(format!(
+        "provenance\t{}\t{}",
+        cache_option(provenance.build_id.as_deref()),
+        cache_option(provenance.comp_dir.as_deref())
+    ));
+    // the .dwo files aren't part of the key, they are checked when reading
+    for dwo in dwo_files.iter() {
+        let data = fs::read(dwo)?;
+        let name = dwo.to_str().unwrap_or_default();
+        lines.push(format!(
+            "dwo\t{:016x}\t{}",
+            fnv1a(&data),
+            cache_escape(name)
+        ));
+    }
+    for f in list.iter() {
+        let scope = f
+            .scope
+            .as_ref()
+            .map(|(kind, scope)| format!("{}:{}", kind.long_name(), scope));
+        let mut line = format!(
+            "tag\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            cache_escape(&f.func_name),
+            cache_escape(&f.file_name),
+            f.line_number,
+            f.column_number,
+            f.kind.long_name(),
+            cache_option(scope.as_deref()),
+            cache_option(f.comp_dir.as_deref())
+        );
+        for (name, value) in f.fields.iter() {
+            line.push_str(&format!("\t{}={}", name, cache_escape(value)));
+        }
+        lines.push(line);
+    }
+    let temp_path = path.with_extension(format!("tmp{}", std::process::id()));
+    let result = fs::File::create(&temp_path).and_then(|file| {
+        let mut out = std::io::BufWriter::new(file);
+        write_tags(&mut out, &[], &lines)?;
+        out.into_inner()?.sync_all()
+    });
+    if let Err(e) = result {
+        let _ = fs::remove_file(&temp_path);
+        return Err(e);
+    }
+    fs::rename(&temp_path, path)
+}
+// The tags, provenance and stats stored for a file. None if there is no
+// entry, it is from another version, can't be read, or one of the .dwo files
+// it was made from has changed since.
+fn read_cache(
+    path: &std::path::Path,
+    input_file: &str,
+) -> Option<(Vec<FunctionInfo>, Provenance, Stats)> {
+    let text = fs::read_to_string(path).ok()?;
+    let mut lines = text.lines();
+    if lines.next()? != format!("dwarf-easy-cache\t{}", CACHE_VERSION) {
+        return None;
+    }
+    let mut list = Vec::new();
+    let mut stats = Stats::default();
+    let mut provenance = Provenance {
+        input_file: input_file.to_string(),
+        build_id: None,
+        comp_dir: None,
+    };
+    for line in lines {
+        let values: Vec<&str> = line.split('\t').collect();
+        match values.as_slice() {
+            ["stats", units, subprograms, relocatable] => {
+                stats.units = units.parse().ok()?;
+                stats.subprograms = subprograms.parse().ok()?;
+                stats.relocatable = *relocatable == "1";
+            }
+            ["version", version, count] => {
+                stats
+                    .unit_versions
+                    .insert(version.parse().ok()?, count.parse().ok()?);
+            }
+            ["provenance", build_id, comp_dir] => {
+                provenance.build_id = cache_parse_option(build_id)?;
+                provenance.comp_dir = cache_parse_option(comp_dir)?;
+            }
+            ["dwo", hash, name] => {
+                let data = fs::read(cache_unescape(name)).ok()?;
+                if format!("{:016x}", fnv1a(&data)) != *hash {
+                    return None;
+                }
+            }
+            ["tag", name, file, line, column, kind, scope, comp_dir, fields @ ..] => {
+                let scope = match cache_parse_option(scope)? {
+                    Some(scope) => {
+                        let (kind, scope) = scope.split_once(':')?;
+                        Some((Kind::from_long_name(kind)?, scope.to_string()))
+                    }
+                    None => None,
+                };
+                let mut tag_fields = Vec::new();
+                for field in fields.iter() {
+                    let (name, value) = field.split_once('=')?;
+                    let (name, _) = ALL_FIELDS.iter().find(|(n, _)| *n == name)?;
+                    tag_fields.push((*name, cache_unescape(value)));
+                }
+                list.push(FunctionInfo {
+                    func_name: cache_unescape(name),
+                    file_name: cache_unescape(file),
+                    line_number: line.parse().ok()?,
+                    column_number: column.parse().ok()?,
+                    kind: Kind::from_long_name(kind)?,
+                    scope,
+                    fields: tag_fields,
+                    comp_dir: cache_parse_option(comp_dir)?,
+                });
+            }
+            _otherwise => return None,
+        }
+    }
+    Some((list, provenance, stats))
+}
+// Update a tags file in place. The merged tags are written to a temporary
+// file next to it which is then renamed over the original, so an interrupted
+// run leaves the original tags file untouched.
+fn merge_into(
+    path: &str,
+    file_info_list: &[FunctionInfo],
+    header: &[String],
+    options: &Options,
+    sources: &mut SourceCache,
+) -> std::io::Result<()> {
+    let new_lines: Vec<String> = file_info_list
+        .iter()
+        .map(|f| format_file_info(f, sources, options))
+        .collect();
+    write_merged(path, &new_lines, file_info_list, header, options)
+}
+// Merge the formatted lines of file_info_list into the tags file at path
+fn write_merged(
+    path: &str,
+    new_lines: &[String],
+    file_info_list: &[FunctionInfo],
+    header: &[String],
+    options: &Options,
+) -> std::io::Result<()> {
+    let existing = match fs::read_to_string(path) {
+        // the file may start with a BOM, from --output-bom or an editor
+        Ok(s) => s.strip_prefix('\u{feff}').map(str::to_string).unwrap_or(s),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e),
+    };
+    let mut new_files: Vec<&str> = file_info_list
+        .iter()
+        .map(|f| f.file_name.as_str())
+        .collect();
+    new_files.sort();
+    new_files.dedup();
+    let lines = merge_tag_lines(&existing, new_lines, &new_files, options.collation);
+    replace_file(path, |out| {
+        if options.output_bom {
+            out.write_all(UTF8_BOM)?;
+        }
+        write_tags(out, header, &lines)
+    })
+}
+// Write a file through a temporary file next to it which is then renamed
+// over it, so a write that fails partway leaves the old file as it was
+fn replace_file(
+    path: &str,
+    write: impl FnOnce(&mut std::io::BufWriter<fs::File>) -> std::io::Result<()>,
+) -> std::io::Result<()> {
+    let temp_path = format!("{}.tmp{}", path, std::process::id());
+    let result = fs::File::create(&temp_path).and_then(|file| {
+        let mut out = std::io::BufWriter::new(file);
+        write(&mut out)?;
+        out.into_inner()?.sync_all()
+    });
+    if let Err(e) = result {
+        // the original is still intact, just clean up after ourselves
+        let _ = fs::remove_file(&temp_path);
+        return Err(e);
+    }
+    fs::rename(&temp_path, path)
+}
+// How long the sources have to stay unchanged before --watch-sources
+// rewrites the tags, editors often save a file in several steps
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(200);
+// The absolute path of a file through its directory, so it still works once
+// the file itself is gone, as when an editor saves by renaming over it
+fn watch_key(path: &std::path::Path) -> Option<std::path::PathBuf> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => std::path::Path::new("."),
+    };
+    Some(fs::canonicalize(dir).ok()?.join(path.file_name()?))
+}
+// The tag lines --watch-sources keeps up to date, with the source file each
+// one's pattern was read from
+struct WatchedTags<'a> {
+    file_info_list: &'a [FunctionInfo],
+    paths: Vec<Option<std::path::PathBuf>>,
+    lines: Vec<String>,
+    sources: SourceCache,
+}
+impl<'a> WatchedTags<'a> {
+    fn new(file_info_list: &'a [FunctionInfo], options: &Options) -> Self {
+        let mut sources = SourceCache::default();
+        let paths = file_info_list.iter().map(|f| sources.path(f)).collect();
+        let lines = file_info_list
+            .iter()
+            .map(|f| format_file_info(f, &mut sources, options))
+            .collect();
+        WatchedTags {
+            file_info_list,
+            paths,
+            lines,
+            sources,
+        }
+    }
+    // Read the changed files again, format the tags from them and merge the
+    // lines into target as --merge-into does
+    fn refresh(
+        &mut self,
+        changed: &HashSet<std::path::PathBuf>,
+        target: &str,
+        header: &[String],
+        options: &Options,
+    ) -> std::io::Result<()> {
+        for path in changed.iter() {
+            self.sources.forget(path);
+        }
+        for (i, f) in self.file_info_list.iter().enumerate() {
+            if self.paths[i].as_ref().is_some_and(|p| changed.contains(p)) {
+                self.lines[i] = format_file_info(f, &mut self.sources, options);
+            }
+        }
+        write_merged(target, &self.lines, self.file_info_list, header, options)
+    }
+}
+// --watch-sources: keep the search patterns of the tags file at target up to
+// date while the sources are edited. Only the tags of a source file that
+// changed are formatted again, from its new lines, the DWARF isn't read
+// again. Returns only when watching fails.
+fn watch_sources(
+    target: &str,
+    file_info_list: &[FunctionInfo],
+    header: &[String],
+    options: &Options,
+) -> Result<(), String> {
+    let mut tags = WatchedTags::new(file_info_list, options);
+    // the source files by the key their events are matched on. Their
+    // directories are watched, a watch on a file is lost when it is replaced.
+    let mut watched: HashMap<std::path::PathBuf, std::path::PathBuf> = HashMap::new();
+    for path in tags.paths.iter().flatten() {
+        if let Some(key) = watch_key(path) {
+            watched.insert(key, path.clone());
+        }
+    }
+    let dirs: BTreeSet<&std::path::Path> = watched.keys().filter_map(|k| k.parent()).collect();
+    let (sender, receiver) = std::sync::mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender).map_err(|e| e.to_string())?;
+    for dir in dirs {
+        notify::Watcher::watch(&mut watcher, dir, notify::RecursiveMode::NonRecursive)
+            .map_err(|e| format!("could not watch {}: {}", dir.display(), e))?;
+    }
+    eprintln!(
+        "watching {} source file(s) for changes, {} is updated until stopped",
+        watched.len(),
+        target
+    );
+    loop {
+        let mut events = vec![receiver.recv().map_err(|e| e.to_string())?];
+        while let Ok(event) = receiver.recv_timeout(WATCH_DEBOUNCE) {
+            events.push(event);
+        }
+        let mut changed: HashSet<std::path::PathBuf> = HashSet::new();
+        for event in events {
+            let event = event.map_err(|e| e.to_string())?;
+            // reading the sources to format the tags gives access events
+            if !matches!(
+                event.kind,
+                notify::EventKind::Create(_)
+                    | notify::EventKind::Modify(_)
+                    | notify::EventKind::Remove(_)
+            ) {
+                continue;
+            }
+            changed.extend(
+                event
+                    .paths
+                    .iter()
+                    .filter_map(|p| watched.get(&watch_key(p)?).cloned()),
+            );
+        }
+        if changed.is_empty() {
+            continue;
+        }
+        tags.refresh(&changed, target, header, options)
+            .map_err(|e| format!("could not update {}: {}", target, e))?;
+        eprintln!(
+            "updated {} after changes to {} file(s)",
+            target,
+            changed.len()
+        );
+    }
+}
+// load_file_section want's to return an empty array when a section isn't found
+static EMPTY_ARRAY: [u8; 0] = [0; 0];
+// elf::abi has no i386 relocation types
+const R_386_32: u32 = 1;
+// The size in bytes of the relocations that just store a symbol's value plus
+// an addend, these are the only ones the debug sections use for offsets and
+// addresses
+fn absolute_relocation_size(machine: u16, r_type: u32) -> Option<usize> {
+    use elf::abi;
+    match (machine, r_type) {
+        (abi::EM_X86_64, abi::R_X86_64_64) => Some(8),
+        (abi::EM_X86_64, abi::R_X86_64_32) | (abi::EM_X86_64, abi::R_X86_64_32S) => Some(4),
+        (abi::EM_AARCH64, abi::R_AARCH64_ABS64) => Some(8),
+        (abi::EM_AARCH64, abi::R_AARCH64_ABS32) => Some(4),
+        (abi::EM_RISCV, abi::R_RISCV_64) => Some(8),
+        (abi::EM_RISCV, abi::R_RISCV_32) => Some(4),
+        (abi::EM_PPC64, abi::R_PPC64_ADDR64) => Some(8),
+        (abi::EM_PPC64, abi::R_PPC64_ADDR32) => Some(4),
+        (abi::EM_ARM, abi::R_ARM_ABS32) => Some(4),
+        (abi::EM_386, R_386_32) => Some(4),
+        _otherwise => None,
+    }
+}
+fn read_uint(bytes: &[u8], big_endian: bool) -> u64 {
+    let mut value = 0u64;
+    for i in 0..bytes.len() {
+        let b = if big_endian {
+            bytes[i]
+        } else {
+            bytes[bytes.len() - 1 - i]
+        };
+        value = (value << 8) | u64::from(b);
+    }
+    value
+}
+fn write_uint(bytes: &mut [u8], value: u64, big_endian: bool) {
+    let len = bytes.len();
+    for i in 0..len {
+        let b = (value >> (8 * i)) as u8;
+        if big_endian {
+            bytes[len - 1 - i] = b;
+        } else {
+            bytes[i] = b;
+        }
+    }
+}
+// In a relocatable object (.o) the debug sections aren't finished yet, e.g.
+// on x86_64 every .debug_str offset is 0 until its relocation is applied.
+// Apply the relocations that target the section to a copy of its data.
+// Addresses end up relative to the start of the section they point into.
+fn relocate_section(
+    file: &elf::ElfBytes<'_, elf::endian::AnyEndian>,
+    name: &str,
+    data: &[u8],
+    stats: &mut Stats,
+) -> Vec<u8> {
+    let mut data = data.to_vec();
+    let big_endian = file.ehdr.endianness == elf::endian::AnyEndian::Big;
+    let Ok((Some(shdrs), Some(strtab))) = file.section_headers_with_strtab() else {
+        return data;
+    };
+    let Some(index) = shdrs
+        .iter()
+        .position(|shdr| strtab.get(shdr.sh_name as usize).ok() == Some(name))
+    else {
+        return data;
+    };
+    let (symtab, _) = match file.symbol_table() {
+        Ok(Some(symtab)) => symtab,
+        Ok(None) => return data,
+        Err(e) => {
+            stats.warn(&format!(
+                "the symbol table is broken: {}, {} is left unrelocated",
+                e, name
+            ));
+            return data;
+        }
+    };
+    // (offset, symbol, type, addend), REL relocations keep the addend in place
+    let mut relocations: Vec<(u64, u32, u32, Option<i64>)> = Vec::new();
+    for shdr in shdrs.iter().filter(|shdr| shdr.sh_info as usize == index) {
+        match shdr.sh_type {
+            elf::abi::SHT_RELA => match file.section_data_as_relas(&shdr) {
+                Ok(relas) => relocations
+                    .extend(relas.map(|r| (r.r_offset, r.r_sym, r.r_type, Some(r.r_addend)))),
+                Err(e) => stats.warn(&format!(
+                    "the relocations of {} are broken: {}, leaving them out",
+                    name, e
+                )),
+            },
+            elf::abi::SHT_REL => match file.section_data_as_rels(&shdr) {
+                Ok(rels) => relocations.extend(rels.map(|r| (r.r_offset, r.r_sym, r.r_type, None))),
+                Err(e) => stats.warn(&format!(
+                    "the relocations of {} are broken: {}, leaving them out",
+                    name, e
+                )),
+            },
+            _otherwise => {}
+        }
+    }
+    let mut out_of_range = 0;
+    for (offset, sym, r_type, addend) in relocations {
+        let Some(size) = absolute_relocation_size(file.ehdr.e_machine, r_type) else {
+            continue;
+        };
+        // a broken r_offset can be anywhere, even near the end of the
+        // address space
+        let bytes = usize::try_from(offset)
+            .ok()
+            .and_then(|start| Some(start..start.checked_add(size)?))
+            .and_then(|range| data.get_mut(range));
+        let Some(bytes) = bytes else {
+            out_of_range += 1;
+            continue;
+        };
+        let sym_value = symtab.get(sym as usize).map(|s| s.st_value).unwrap_or(0);
+        let addend = match addend {
+            Some(a) => a as u64,
+            None => read_uint(bytes, big_endian),
+        };
+        write_uint(bytes, sym_value.wrapping_add(addend), big_endian);
+    }
+    if out_of_range > 0 {
+        stats.warn(&format!(
+            "{} relocation(s) of {} point outside it, leaving them out",
+            out_of_range, name
+        ));
+    }
+    data
+}
+// The contents of a section, decompressed if it is SHF_COMPRESSED. Each
+// section is looked at on its own, toolchains can compress only some of them,
+// e.g. just the string sections. A section that can't be decompressed, or
+// whose header points outside the file, is left empty with a warning, so the
+// rest can still be read.
+fn section_bytes<'input>(
+    file: &elf::ElfBytes<'input, elf::endian::AnyEndian>,
+    name: &str,
+    section_header: &elf::section::SectionHeader,
+    stats: &mut Stats,
+) -> Cow<'input, [u8]> {
+    let (data, compression) = match file.section_data(section_header) {
+        Ok(section) => section,
+        Err(e) => {
+            stats.warn(&format!(
+                "the section header of {} (offset 0x{:x}, size 0x{:x}) is broken: {}, leaving it out",
+                name, section_header.sh_offset, section_header.sh_size, e
+            ));
+            return Cow::Borrowed(&EMPTY_ARRAY);
+        }
+    };
+    let Some(compression) = compression else {
+        return Cow::Borrowed(data);
+    };
+    // ch_size is only believed as far as the data goes, a broken header
+    // can't make the decompression use more memory than it says
+    let mut decompressed = Vec::new();
+    let limit = compression.ch_size.saturating_add(1);
+    let result = match compression.ch_type {
+        elf::abi::ELFCOMPRESS_ZLIB => std::io::Read::read_to_end(
+            &mut std::io::Read::take(flate2::read::ZlibDecoder::new(data), limit),
+            &mut decompressed,
+        )
+        .map_err(|e| e.to_string()),
+        elf::abi::ELFCOMPRESS_ZSTD => ruzstd::decoding::StreamingDecoder::new(data)
+            .map_err(|e| e.to_string())
+            .and_then(|d| {
+                std::io::Read::read_to_end(&mut std::io::Read::take(d, limit), &mut decompressed)
+                    .map_err(|e| e.to_string())
+            }),
+        other => Err(format!("unknown compression type {}", other)),
+    };
+    match result {
+        Ok(_) if decompressed.len() as u64 == compression.ch_size => Cow::Owned(decompressed),
+        Ok(_) => {
+            stats.warn(&format!(
+                "{} decompressed to {} bytes instead of {}, leaving it out",
+                name,
+                decompressed.len(),
+                compression.ch_size
+            ));
+            Cow::Borrowed(&EMPTY_ARRAY)
+        }
+        Err(e) => {
+            stats.warn(&format!(
+                "could not decompress {}: {}, leaving it out",
+                name, e
+            ));
+            Cow::Borrowed(&EMPTY_ARRAY)
+        }
+    }
+}
+// The header of the section called name, a broken section header table or
+// section name table gives a warning and no section
+fn section_header(
+    file: &elf::ElfBytes<'_, elf::endian::AnyEndian>,
+    name: &str,
+    stats: &mut Stats,
+) -> Option<elf::section::SectionHeader> {
+    match file.section_header_by_name(name) {
+        Ok(section_header) => section_header,
+        Err(e) => {
+            stats.warn(&format!(
+                "could not look up {}: {}, leaving it out",
+                name, e
+            ));
+            None
+        }
+    }
+}
+// Like load_file_section but for the sections of a .dwo file
+fn load_dwo_section<'input>(
+    section: gimli::SectionId,
+    file: &elf::ElfBytes<'input, elf::endian::AnyEndian>,
+    stats: &mut Stats,
+) -> gimli::Result<Cow<'input, [u8]>> {
+    let Some(name) = section.dwo_name() else {
+        return Ok(Cow::Borrowed(&EMPTY_ARRAY));
+    };
+    match section_header(file, name, stats) {
+        Some(section_header) => Ok(section_bytes(file, name, &section_header, stats)),
+        None => Ok(Cow::Borrowed(&EMPTY_ARRAY)),
+    }
+}
+// Create a function that gimli can call
+// receives the requested section
+// returns the requested section data
+//
+// The life time of the input file's data outlives this function
+fn load_file_section<'input>(
+    section: gimli::SectionId,
+    file: &elf::ElfBytes<'input, elf::endian::AnyEndian>,
+    stats: &mut Stats,
+) -> gimli::Result<Cow<'input, [u8]>> {
+    // Get the requested section header
+    let sec = section_header(file, section.name(), stats);
+    if let Some(section_header) = sec {
+        let section_data = section_bytes(file, section.name(), &section_header, stats);
+        // a section left out as broken has nothing to relocate
+        if file.ehdr.e_type == elf::abi::ET_REL && !section_data.is_empty() {
+            // relocation offsets are into the decompressed data
+            return Ok(Cow::Owned(relocate_section(
+                file,
+                section.name(),
+                &section_data,
+                stats,
+            )));
+        }
+        // Return the found data
+        Ok(section_data)
+    } else {
+        // No section header was found return the empty array
+        Ok(Cow::Borrowed(&EMPTY_ARRAY))
+    }
+}
+// Clean up a path without touching the file system, "a/./b//../c" -> "a/c".
+// Both / and \ are treated as separators and / is used in the result.
+fn normalize_path(path: &str) -> String {
+    let absolute = path.starts_with('/') || path.starts_with('\\');
+    let mut parts: Vec<&str> = Vec::new();
+    for part in path.split(['/', '\\']) {
+        match part {
+            "" | "." => {}
+            ".." => match parts.last() {
+                Some(&last) if last != ".." => {
+                    parts.pop();
+                }
+                // there is nothing above the root
+                _ if absolute => {}
+                _ => parts.push(".."),
+            },
+            _ => parts.push(part),
+        }
+    }
+    let joined = parts.join("/");
+    if absolute {
+        format!("/{}", joined)
+    } else if joined.is_empty() {
+        ".".to_string()
+    } else {
+        joined
+    }
+}
+// Read a newline separated list of source files, normalized for comparison
+fn read_file_manifest(path: &str) -> std::io::Result<Vec<String>> {
+    let manifest = fs::read_to_string(path)?;
+    Ok(manifest
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .map(normalize_path)
+        .collect())
+}
+// What to do when the elf file produced no tags, usually because it was
+// built without -g
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum OnEmpty {
+    // exit 0 and print nothing extra
+    Ignore,
+    // exit 0 and print a message on stderr
+    Warn,
+    // exit 1 and print a message on stderr
+    Error,
+}
+// Command line options
+struct Options {
+    path: String,
+    on_empty: OnEmpty,
+    stats: bool,
+    follow_origin_for_location: bool,
+    merge_into: Option<String>,
+    ada_names: bool,
+    kind_map: BTreeMap<Kind, char>,
+    // the kinds to write tags for, in units of languages without their own
+    kinds: Vec<Kind>,
+    // the kinds to write tags for in the units of a language
+    kinds_by_language: Vec<(&'static str, Vec<Kind>)>,
+    profile: bool,
+    // the manifest of source files to keep tags for and the files it lists
+    include_only_files: Option<(String, Vec<String>)>,
+    emit_provenance: bool,
+    no_pseudo_tags: bool,
+    excmd: ExCmd,
+    tabs_to_spaces: Option<TabReplace>,
+    line_zero: LineZero,
+    collation: Collation,
+    rust_closures: RustClosures,
+    // fail when a source file needed for a pattern or doc comment is missing
+    fail_on_missing_source: bool,
+    format: Format,
+    // how many references to follow from one DIE
+    max_depth: usize,
+    // stop after this many units of each file
+    max_units: Option<usize>,
+    group_by_directory: Option<String>,
+    group_depth: usize,
+    no_header: bool,
+    dwo_dir: Option<String>,
+    // exit with this code if any warning was printed
+    exit_code_on_warnings: Option<i32>,
+    // write each unit's tags as it is walked instead of sorting them all
+    unsorted: bool,
+    dedup_window: usize,
+    // the extension fields to write, from ALL_FIELDS
+    fields: Vec<&'static str>,
+    load_bias: Option<u64>,
+    // compare: the new file, path is the old one
+    compare_with: Option<String>,
+    // merge: path is a directory of tags files to combine
+    merge_dir: bool,
+    // joins the parts of a scope, by default what the unit's language uses
+    scope_separator: Option<String>,
+    cache_dir: Option<String>,
+    // clean up the file names written, without looking at the file system
+    normalize_paths: bool,
+    // write Foo::method for a method of Foo<int>
+    strip_template_args: bool,
+    // put anonymous namespaces and types in the scope, under the unit's file
+    group_anonymous_by_file: bool,
+    // only tag functions with an address, leaving out the ones without code
+    only_with_code: bool,
+    // the supplementary file, instead of the one .debug_sup names
+    sup_file: Option<String>,
+    // where to write the tags removed as duplicates
+    dedup_report: Option<String>,
+    // keep updating the patterns of the --merge-into file as sources change
+    watch_sources: bool,
+    // start the tags files written with a UTF-8 byte order mark
+    output_bom: bool,
+}
+const USAGE: &str = "usage: dwarf-easy [options] <elf_file>
+       dwarf-easy compare [options] <old_elf_file> <new_elf_file>
+       dwarf-easy merge [options] <tags_dir>
+
+options:
+  --on-empty <ignore|warn|error>  what to do when no tags are found (default: warn)
+  --stats                         print a summary of the debug info on stderr
+  --follow-origin-for-location    take decl file/line from the abstract origin
+                                  instead of the instance
+  --merge-into <tagsfile>         update an existing tags file instead of
+                                  printing the tags
+  --ada-names                     turn GNAT names like pkg__proc back into
+                                  Ada names
+  --kind-map <kind=letter,...>    change the letter written for a kind,
+                                  e.g. function=F
+  --kinds <letters>               only write tags of these kinds, e.g. fN
+                                  (default: every kind except N and v)
+  --kinds <lang:letters,...>      the kinds to write for each language, e.g.
+                                  c:fvd,c++:fN,*:f
+  --profile                       print the time spent in each phase on stderr
+  --include-only-files <manifest> only write tags for the source files listed,
+                                  one per line, in the manifest
+  --only-with-code                only tag functions that have compiled code,
+                                  leaving out declarations and inline-only ones
+  --emit-provenance               write the build-id, compilation directory
+                                  and input file as pseudo tags
+  --no-pseudo-tags                leave out every pseudo tag except the
+                                  format and sorted lines
+  --no-header                     leave out every pseudo tag, only write tags
+  --format <ctags|outline>        write a tags file (default) or an outline of
+                                  each source file
+  --format <text|json>            compare: write the changes as text (default)
+                                  or JSON
+  --max-depth <N>                 how many references to follow from one DIE
+                                  (default: 64)
+  --max-units <N>                 read only the first N units of each file, for
+                                  a quick sample of a large one
+  --group-by-directory <out-dir>  write one tags file per source directory
+  --group-depth <N>               how many directories make up a group
+                                  (default: 1)
+  --dwo-dir <dir>                 look for split DWARF .dwo files here first
+  --sup-file <file>               the supplementary file with the debug info
+                                  shared with other files (default: the one
+                                  .debug_sup or .gnu_debugaltlink names)
+  --exit-code-on-warnings <N>     exit with N if any warning was printed,
+                                  the tags are still written
+  --werror                        same as --exit-code-on-warnings 1
+  --unsorted                      write each unit's tags as soon as it is read,
+                                  without sorting or removing duplicates
+  --dedup-window <N>              with --unsorted, leave out repeats of the
+                                  last N tags written (default: 0, none)
+  --dedup-report <file>           write the tags left out as duplicates to file
+  --scope-separator <str>         join the parts of a scope with str (default:
+                                  :: or . depending on the language)
+  --cache-dir <dir>               keep the tags of each input file here and
+                                  reuse them while its contents are the same
+  --normalize-paths               remove ./, ../ and doubled separators from
+                                  the file names, without looking at the files
+  --strip-template-args-from-scope
+                                  leave the template arguments out of scopes,
+                                  Foo<int>::f is scoped under Foo
+  --group-anonymous-by-file       scope what is in an anonymous namespace
+                                  under its file, a.cpp::<anonymous>
+  --fields <[+|-]name,...>        choose the extension fields written, e.g.
+                                  +dwarftag,-visibility
+  --addresses                     same as --fields +address, write the entry
+                                  address of each function
+  --extract-docs                  same as --fields +doc, write the comment
+                                  above each tag in its source
+  --trait-impls                   same as --fields +impl, write the trait impl
+                                  of each Rust method
+  --load-bias <addr>              with --addresses, write addresses as if the
+                                  file was loaded at addr
+  --excmd <number|pattern>        find tags by line number (default) or by a
+                                  search pattern read from the source
+  --watch-sources                 with --excmd pattern and --merge-into, keep
+                                  updating the patterns while the sources are
+                                  edited, until stopped
+  --output-bom                    start the files written by --merge-into or
+                                  --group-by-directory with a UTF-8 BOM
+  --fail-on-missing-source        with --excmd pattern or --extract-docs, fail
+                                  if a source file can't be read
+  --tabs-to-spaces <N|escape>     replace tabs in patterns with N spaces or \\t
+  --rust-closures <skip|keep|qualify>
+                                  leave out Rust closures and shims (default),
+                                  tag them as {closure#0}, or as f::{closure#0}
+  --line-zero <keep|as-one|omit>  how tags with an unknown line (0) are written:
+                                  :0 (default), :1 or a search for the name
+  --collation <byte|fold>         sort tag names by their bytes (default) or
+                                  alphabetically, ignoring case and the
+                                  accents of Latin letters
+  --list-kinds                    print the kinds of tags written for each
+                                  language, with their letters, and exit
+  -h, --help                      print this help";
+// --fail-on-missing-source: list the sources that couldn't be read for a
+// pattern or doc comment and fail
+fn check_missing_sources(options: &Options, missing: &BTreeSet<String>) {
+    if !options.fail_on_missing_source || missing.is_empty() {
+        return;
+    }
+    eprintln!("error: {} source file(s) could not be read:", missing.len());
+    for file in missing.iter() {
+        eprintln!("  {}", file);
+    }
+    std::process::exit(EXIT_FAILURE);
+}
+// --on-empty: a run that found no tags warns, which --exit-code-on-warnings
+// then counts, or fails with the message
+fn check_empty(options: &Options, stats: &mut Stats) -> Result<(), String> {
+    if stats.tags > 0 {
+        return Ok(());
+    }
+    let msg = if options.merge_dir {
+        format!("no tags found in the tags files in {}", options.path)
+    } else {
+        format!(
+            "no tags found in {}, was it built with debug info (-g)?",
+            options.path
+        )
+    };
+    match options.on_empty {
+        OnEmpty::Ignore => Ok(()),
+        OnEmpty::Warn => {
+            stats.warn(&msg);
+            Ok(())
+        }
+        OnEmpty::Error => Err(msg),
+    }
+}
+// --exit-code-on-warnings: the message and exit code of a run that warned
+fn check_warnings(options: &Options, stats: &Stats) -> Result<(), (String, i32)> {
+    match options.exit_code_on_warnings {
+        Some(code) if !stats.warnings.is_empty() => Err((
+            format!("{} warning(s) were printed", stats.warnings.len()),
+            code,
+        )),
+        _ => Ok(()),
+    }
+}
+// Exit code used for bad command line arguments
+const EXIT_USAGE: i32 = 2;
+// Exit code used when the run failed, e.g. --on-empty error with no tags
+const EXIT_FAILURE: i32 = 1;
+fn usage_error(msg: &str) -> ! {
+    eprintln!("error: {}\n\n{}", msg, USAGE);
+    std::process::exit(EXIT_USAGE);
+}
+// Get the value of an option, either from "--opt=value" or the next argument
+fn option_value(
+    name: &str,
+    inline: Option<&str>,
+    args: &mut impl Iterator<Item = String>,
+) -> String {
+    match inline {
+        Some(v) => v.to_string(),
+        None => args
+            .next()
+            .unwrap_or_else(|| usage_error(&format!("{} needs a value", name))),
+    }
+}
+// Parse "function=F,subprogram=R" into kind letter overrides
+// --tabs-to-spaces: escape or a number of spaces, None for anything else
+fn parse_tabs_to_spaces(value: &str) -> Option<TabReplace> {
+    match (value, value.parse::<usize>()) {
+        ("escape", _) => Some(TabReplace::Escape),
+        (_, Ok(n)) => Some(TabReplace::Spaces(n)),
+        _ => None,
+    }
+}
+fn parse_kind_map(value: &str) -> BTreeMap<Kind, char> {
+    let mut kind_map = BTreeMap::new();
+    for item in value.split(',').filter(|i| !i.is_empty()) {
+        let (name, letter) = item.split_once('=').unwrap_or_else(|| {
+            usage_error(&format!("--kind-map expects kind=letter, got '{}'", item))
+        });
+        let kind = Kind::from_long_name(name)
+            .unwrap_or_else(|| usage_error(&format!("unknown kind '{}' in --kind-map", name)));
+        let mut chars = letter.chars();
+        let letter = match (chars.next(), chars.next()) {
+            (Some(c), None) if c.is_ascii_alphabetic() => c,
+            _ => usage_error(&format!(
+                "--kind-map letter for {} must be a single letter",
+                name
+            )),
+        };
+        kind_map.insert(kind, letter);
+    }
+    // two kinds with the same letter couldn't be told apart
+    for (i, a) in ALL_KINDS.iter().enumerate() {
+        for b in ALL_KINDS.iter().skip(i + 1) {
+            let letter_a = *kind_map.get(a).unwrap_or(&a.letter());
+            let letter_b = *kind_map.get(b).unwrap_or(&b.letter());
+            if letter_a == letter_b {
+                usage_error(&format!(
+                    "--kind-map gives {} and {} the same letter '{}'",
+                    a.long_name(),
+                    b.long_name(),
+                    letter_a
+                ));
+            }
+        }
+    }
+    kind_map
+}
+// The letters given to --kinds are the ones written, after --kind-map
+fn parse_kind_letters(letters: &str, kind_map: &BTreeMap<Kind, char>) -> Vec<Kind> {
+    letters
+        .chars()
+        .map(|c| {
+            ALL_KINDS
+                .iter()
+                .copied()
+                .find(|k| *kind_map.get(k).unwrap_or(&k.letter()) == c)
+                .unwrap_or_else(|| usage_error(&format!("unknown kind letter '{}'", c)))
+        })
+        .collect()
+}
+// --kinds is either the letters of every unit's kinds, or a list of
+// language:letters, e.g. "c:fvd,c++:fN". Languages not in the list get the
+// kinds of a "*:letters" entry, or the default kinds without one.
+fn parse_kinds(
+    value: Option<&str>,
+    kind_map: &BTreeMap<Kind, char>,
+) -> (Vec<Kind>, Vec<(&'static str, Vec<Kind>)>) {
+    let default_kinds: Vec<Kind> = ALL_KINDS
+        .iter()
+        .copied()
+        .filter(|k| k.enabled_by_default())
+        .collect();
+    let Some(value) = value else {
+        return (default_kinds, Vec::new());
+    };
+    if !value.contains(':') {
+        return (parse_kind_letters(value, kind_map), Vec::new());
+    }
+    let mut fallback = default_kinds;
+    let mut by_language = Vec::new();
+    for item in value.split(',').filter(|i| !i.is_empty()) {
+        let (language, letters) = item.split_once(':').unwrap_or_else(|| {
+            usage_error(&format!("--kinds expects language:letters, got '{}'", item))
+        });
+        let kinds = parse_kind_letters(letters, kind_map);
+        if language == "*" {
+            fallback = kinds;
+            continue;
+        }
+        let language = language.to_ascii_lowercase();
+        let language = KIND_LANGUAGE_ALIASES
+            .iter()
+            .find(|(alias, _)| *alias == language)
+            .map_or(language.as_str(), |(_, name)| name);
+        let Some(name) = KIND_LANGUAGES.iter().find(|n| **n == language) else {
+            usage_error(&format!(
+                "unknown language '{}' in --kinds, the languages are {}",
+                language,
+                KIND_LANGUAGES.join(", ")
+            ));
+        };
+        by_language.push((*name, kinds));
+    }
+    (fallback, by_language)
+}
+// --fields: a list of field names replaces the default fields, names starting
+// with + or - add to or take away from them
+// --load-bias moves the address field, so it needs one
+fn check_load_bias(load_bias: Option<u64>, fields: &[&str]) -> Result<(), &'static str> {
+    if load_bias.is_some() && !fields.contains(&"address") {
+        return Err("--load-bias only works with --addresses");
+    }
+    Ok(())
+}
+fn parse_fields(
+    value: Option<&str>,
+    addresses: bool,
+    docs: bool,
+    trait_impls: bool,
+) -> Vec<&'static str> {
+    let mut fields: Vec<&'static str> = ALL_FIELDS
+        .iter()
+        .filter(|(_, default)| *default)
+        .map(|(name, _)| *name)
+        .collect();
+    if let Some(value) = value {
+        let items: Vec<&str> = value.split(',').filter(|i| !i.is_empty()).collect();
+        if items
+            .first()
+            .is_some_and(|i| !i.starts_with('+') && !i.starts_with('-'))
+        {
+            fields.clear();
+        }
+        for item in items {
+            let (add, name) = match item.strip_prefix('-') {
+                Some(name) => (false, name),
+                None => (true, item.strip_prefix('+').unwrap_or(item)),
+            };
+            let Some((name, _)) = ALL_FIELDS.iter().find(|(n, _)| *n == name) else {
+                usage_error(&format!("unknown field '{}'", name));
+            };
+            fields.retain(|f| f != name);
+            if add {
+                fields.push(name);
+            }
+        }
+    }
+    if addresses && !fields.contains(&"address") {
+        fields.push("address");
+    }
+    if docs && !fields.contains(&"doc") {
+        fields.push("doc");
+    }
+    if trait_impls && !fields.contains(&"impl") {
+        fields.push("impl");
+    }
+    fields
+}
+fn parse_args(args: impl Iterator<Item = String>) -> Options {
+    let mut args = args.peekable();
+    let compare = args.next_if(|a| a == "compare").is_some();
+    let merge_dir = !compare && args.next_if(|a| a == "merge").is_some();
+    let mut path: Option<String> = None;
+    let mut compare_with: Option<String> = None;
+    let mut on_empty = OnEmpty::Warn;
+    let mut stats = false;
+    let mut follow_origin_for_location = false;
+    let mut merge_into: Option<String> = None;
+    let mut ada_names = false;
+    let mut kind_map = BTreeMap::new();
+    let mut kinds: Option<String> = None;
+    let mut profile = false;
+    let mut include_only_files: Option<String> = None;
+    let mut emit_provenance = false;
+    let mut no_pseudo_tags = false;
+    let mut excmd = ExCmd::Number;
+    let mut tabs_to_spaces: Option<TabReplace> = None;
+    let mut line_zero = LineZero::Keep;
+    let mut collation = Collation::Byte;
+    let mut rust_closures = RustClosures::Skip;
+    let mut fail_on_missing_source = false;
+    let mut format: Option<Format> = None;
+    let mut max_depth = DEFAULT_MAX_DEPTH;
+    let mut max_units: Option<usize> = None;
+    let mut group_by_directory: Option<String> = None;
+    let mut group_depth = 1;
+    let mut no_header = false;
+    let mut dwo_dir: Option<String> = None;
+    let mut sup_file: Option<String> = None;
+    let mut dedup_report: Option<String> = None;
+    let mut exit_code_on_warnings: Option<i32> = None;
+    let mut unsorted = false;
+    let mut dedup_window: Option<usize> = None;
+    let mut addresses = false;
+    let mut extract_docs = false;
+    let mut trait_impls = false;
+    let mut fields: Option<String> = None;
+    let mut scope_separator: Option<String> = None;
+    let mut cache_dir: Option<String> = None;
+    let mut normalize_paths = false;
+    let mut strip_template_args = false;
+    let mut group_anonymous_by_file = false;
+    let mut only_with_code = false;
+    let mut watch_sources = false;
+    let mut output_bom = false;
+    let mut list_kinds = false;
+    let mut load_bias: Option<u64> = None;
+    while let Some(arg) = args.next() {
+        // split "--opt=value" into the option and its value
+        let (name, inline) = match arg.split_once('=') {
+            Some((n, v)) if n.starts_with("--") => (n.to_string(), Some(v.to_string())),
+            _ => (arg.clone(), None),
+        };
+        let inline = inline.as_deref();
+        match name.as_str() {
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                std::process::exit(0);
+            }
+            "--on-empty" => {
+                on_empty = match option_value(&name, inline, &mut args).as_str() {
+                    "ignore" => OnEmpty::Ignore,
+                    "warn" => OnEmpty::Warn,
+                    "error" => OnEmpty::Error,
+                    other => usage_error(&format!("unknown --on-empty value '{}'", other)),
+                };
+            }
+            "--stats" => stats = true,
+            "--follow-origin-for-location" => follow_origin_for_location = true,
+            "--merge-into" => merge_into = Some(option_value(&name, inline, &mut args)),
+            "--ada-names" => ada_names = true,
+            "--kind-map" => kind_map = parse_kind_map(&option_value(&name, inline, &mut args)),
+            "--kinds" => kinds = Some(option_value(&name, inline, &mut args)),
+            "--profile" => profile = true,
+            "--emit-provenance" => emit_provenance = true,
+            "--no-pseudo-tags" => no_pseudo_tags = true,
+            "--no-header" => no_header = true,
+            "--dwo-dir" => dwo_dir = Some(option_value(&name, inline, &mut args)),
+            "--sup-file" => sup_file = Some(option_value(&name, inline, &mut args)),
+            "--dedup-report" => dedup_report = Some(option_value(&name, inline, &mut args)),
+            "--exit-code-on-warnings" => {
+                let value = option_value(&name, inline, &mut args);
+                exit_code_on_warnings = match value.parse() {
+                    Ok(code @ 1..=255) => Some(code),
+                    _ => usage_error(&format!(
+                        "--exit-code-on-warnings expects a number from 1 to 255, got '{}'",
+                        value
+                    )),
+                };
+            }
+            "--werror" => exit_code_on_warnings = Some(EXIT_FAILURE),
+            "--unsorted" => unsorted = true,
+            "--addresses" => addresses = true,
+            "--extract-docs" => extract_docs = true,
+            "--trait-impls" => trait_impls = true,
+            "--fields" => fields = Some(option_value(&name, inline, &mut args)),
+            "--cache-dir" => cache_dir = Some(option_value(&name, inline, &mut args)),
+            "--normalize-paths" => normalize_paths = true,
+            "--strip-template-args-from-scope" => strip_template_args = true,
+            "--group-anonymous-by-file" => group_anonymous_by_file = true,
+            "--only-with-code" => only_with_code = true,
+            "--watch-sources" => watch_sources = true,
+            "--output-bom" => output_bom = true,
+            "--list-kinds" => list_kinds = true,
+            "--scope-separator" => scope_separator = Some(option_value(&name, inline, &mut args)),
+            "--load-bias" => {
+                let value = option_value(&name, inline, &mut args);
+                let parsed = match value.strip_prefix("0x").or(value.strip_prefix("0X")) {
+                    Some(hex) => u64::from_str_radix(hex, 16),
+                    None => value.parse(),
+                };
+                load_bias = Some(parsed.unwrap_or_else(|_| {
+                    usage_error(&format!("--load-bias expects an address, got '{}'", value))
+                }));
+            }
+            "--dedup-window" => {
+                let value = option_value(&name, inline, &mut args);
+                dedup_window = Some(value.parse().unwrap_or_else(|_| {
+                    usage_error(&format!("--dedup-window expects a number, got '{}'", value))
+                }));
+            }
+            "--excmd" => {
+                excmd = match option_value(&name, inline, &mut args).as_str() {
+                    "number" => ExCmd::Number,
+                    "pattern" => ExCmd::Pattern,
+                    other => usage_error(&format!("unknown --excmd value '{}'", other)),
+                };
+            }
+            "--fail-on-missing-source" => fail_on_missing_source = true,
+            "--rust-closures" => {
+                rust_closures = match option_value(&name, inline, &mut args).as_str() {
+                    "skip" => RustClosures::Skip,
+                    "keep" => RustClosures::Keep,
+                    "qualify" => RustClosures::Qualify,
+                    other => usage_error(&format!("unknown --rust-closures value '{}'", other)),
+                };
+            }
+            "--line-zero" => {
+                line_zero = match option_value(&name, inline, &mut args).as_str() {
+                    "keep" => LineZero::Keep,
+                    "as-one" => LineZero::AsOne,
+                    "omit" => LineZero::Omit,
+                    other => usage_error(&format!("unknown --line-zero value '{}'", other)),
+                };
+            }
+            "--collation" => {
+                collation = match option_value(&name, inline, &mut args).as_str() {
+                    "byte" => Collation::Byte,
+                    "fold" => Collation::Fold,
+                    other => usage_error(&format!("unknown --collation value '{}'", other)),
+                };
+            }
+            "--format" => {
+                format = match (compare, option_value(&name, inline, &mut args).as_str()) {
+                    (false, "ctags") => Some(Format::Ctags),
+                    (false, "outline") => Some(Format::Outline),
+                    (true, "text") => Some(Format::Text),
+                    (true, "json") => Some(Format::Json),
+                    (_, other) => usage_error(&format!("unknown --format value '{}'", other)),
+                };
+            }
+            "--max-depth" => {
+                let value = option_value(&name, inline, &mut args);
+                max_depth = value.parse().unwrap_or_else(|_| {
+                    usage_error(&format!("--max-depth expects a number, got '{}'", value))
+                });
+            }
+            "--max-units" => {
+                let value = option_value(&name, inline, &mut args);
+                max_units = Some(value.parse().unwrap_or_else(|_| {
+                    usage_error(&format!("--max-units expects a number, got '{}'", value))
+                }));
+            }
+            "--group-by-directory" => {
+                group_by_directory = Some(option_value(&name, inline, &mut args))
+            }
+            "--group-depth" => {
+                let value = option_value(&name, inline, &mut args);
+                group_depth = match value.parse() {
+                    Ok(n) if n > 0 => n,
+                    _ => usage_error(&format!(
+                        "--group-depth expects a number above 0, got '{}'",
+                        value
+                    )),
+                };
+            }
+            "--tabs-to-spaces" => {
+                let value = option_value(&name, inline, &mut args);
+                tabs_to_spaces = Some(parse_tabs_to_spaces(&value).unwrap_or_else(|| {
+                    usage_error(&format!("unknown --tabs-to-spaces value '{}'", value))
+                }));
+            }
+            "--include-only-files" => {
+                include_only_files = Some(option_value(&name, inline, &mut args))
+            }
+            _ if name.starts_with('-') && name.len() > 1 => {
+                usage_error(&format!("unknown option '{}'", name));
+            }
+            _ => {
+                if path.is_none() {
+                    path = Some(arg);
+                } else if compare && compare_with.is_none() {
+                    compare_with = Some(arg);
+                } else if compare {
+                    usage_error("compare takes two elf files");
+                } else if merge_dir {
+                    usage_error("merge takes one directory");
+                } else {
+                    usage_error("only one elf file can be given");
+                }
+            }
+        }
+    }
+    if list_kinds {
+        print!("{}", kind_list(&kind_map));
+        std::process::exit(0);
+    }
+    let (kinds, kinds_by_language) = parse_kinds(kinds.as_deref(), &kind_map);
+    let format = format.unwrap_or(if compare { Format::Text } else { Format::Ctags });
+    if compare && compare_with.is_none() {
+        usage_error("compare takes two elf files");
+    }
+    if compare && (merge_into.is_some() || group_by_directory.is_some() || unsorted) {
+        usage_error("--merge-into, --group-by-directory and --unsorted don't work with compare");
+    }
+    if merge_dir
+        && (merge_into.is_some()
+            || group_by_directory.is_some()
+            || unsorted
+            || format != Format::Ctags)
+    {
+        usage_error(
+            "--merge-into, --group-by-directory, --unsorted and --format outline don't work with merge",
+        );
+    }
+    if merge_into.is_some() && format != Format::Ctags {
+        usage_error("--merge-into only works with --format ctags");
+    }
+    if group_by_directory.is_some() && (merge_into.is_some() || format != Format::Ctags) {
+        usage_error("--group-by-directory only works with --format ctags and without --merge-into");
+    }
+    if unsorted && (merge_into.is_some() || group_by_directory.is_some() || format != Format::Ctags)
+    {
+        usage_error(
+            "--unsorted only works with --format ctags and without --merge-into or --group-by-directory",
+        );
+    }
+    if cache_dir.is_some() && unsorted {
+        usage_error("--cache-dir doesn't work with --unsorted");
+    }
+    if watch_sources && (excmd != ExCmd::Pattern || merge_into.is_none()) {
+        usage_error("--watch-sources only works with --excmd pattern and --merge-into");
+    }
+    if output_bom && merge_into.is_none() && group_by_directory.is_none() {
+        usage_error("--output-bom only works with --merge-into or --group-by-directory");
+    }
+    if dedup_report.is_some() && (compare || unsorted) {
+        usage_error("--dedup-report doesn't work with compare or --unsorted");
+    }
+    if collation != Collation::Byte && unsorted {
+        usage_error("--collation doesn't work with --unsorted");
+    }
+    if dedup_window.is_some() && !unsorted {
+        usage_error("--dedup-window only works with --unsorted");
+    }
+    let fields = parse_fields(fields.as_deref(), addresses, extract_docs, trait_impls);
+    if fail_on_missing_source && excmd != ExCmd::Pattern && !fields.contains(&"doc") {
+        usage_error("--fail-on-missing-source only works with --excmd pattern or --extract-docs");
+    }
+    if let Err(msg) = check_load_bias(load_bias, &fields) {
+        usage_error(msg);
+    }
+    let include_only_files = include_only_files.map(|manifest| {
+        let files = read_file_manifest(&manifest).unwrap_or_else(|e| {
+            eprintln!("error: could not read {}: {}", manifest, e);
+            std::process::exit(EXIT_FAILURE);
+        });
+        (manifest, files)
+    });
+    Options {
+        path: path.unwrap_or_else(|| usage_error("no path given")),
+        on_empty,
+        stats,
+        follow_origin_for_location,
+        merge_into,
+        ada_names,
+        kind_map,
+        kinds,
+        kinds_by_language,
+        profile,
+        include_only_files,
+        emit_provenance,
+        no_pseudo_tags,
+        excmd,
+        tabs_to_spaces,
+        line_zero,
+        collation,
+        rust_closures,
+        fail_on_missing_source,
+        format,
+        max_depth,
+        max_units,
+        group_by_directory,
+        group_depth,
+        no_header,
+        dwo_dir,
+        exit_code_on_warnings,
+        unsorted,
+        dedup_window: dedup_window.unwrap_or(0),
+        fields,
+        load_bias,
+        compare_with,
+        merge_dir,
+        scope_separator,
+        cache_dir,
+        normalize_paths,
+        strip_template_args,
+        group_anonymous_by_file,
+        only_with_code,
+        sup_file,
+        dedup_report,
+        watch_sources,
+        output_bom,
+    }
+}
+// Time spent in each phase of a run, printed by --profile. When profiling is
+// off no clocks are read.
+struct Profile {
+    enabled: bool,
+    phases: Vec<(&'static str, Duration)>,
+}
+impl Profile {
+    fn new(enabled: bool) -> Profile {
+        Profile {
+            enabled,
+            phases: Vec::new(),
+        }
+    }
+    fn start(&self) -> Option<Instant> {
+        if self.enabled {
+            Some(Instant::now())
+        } else {
+            None
+        }
+    }
+    // Add the time since start to a phase, phases can be recorded many times
+    fn record(&mut self, phase: &'static str, start: Option<Instant>) {
+        if let Some(start) = start {
+            let elapsed = start.elapsed();
+            match self.phases.iter_mut().find(|(p, _)| *p == phase) {
+                Some((_, total)) => *total += elapsed,
+                None => self.phases.push((phase, elapsed)),
+            }
+        }
+    }
+    fn print(&self) {
+        let total: Duration = self.phases.iter().map(|(_, d)| *d).sum();
+        for (phase, duration) in self.phases.iter() {
+            let percent = if total.is_zero() {
+                0.0
+            } else {
+                100.0 * duration.as_secs_f64() / total.as_secs_f64()
+            };
+            eprintln!(
+                "{:<16} {:>10.3} ms {:>5.1}%",
+                phase,
+                duration.as_secs_f64() * 1000.0,
+                percent
+            );
+        }
+        eprintln!("{:<16} {:>10.3} ms", "total", total.as_secs_f64() * 1000.0);
+    }
+}
+// Counts collected while walking the debug info, printed by --stats
+#[derive(Default)]
+struct Stats {
+    units: usize,
+    // number of units for each dwarf version, sorted by version
+    unit_versions: BTreeMap<u16, usize>,
+    subprograms: usize,
+    tags: usize,
+    // a .o file, its addresses are relative to their section
+    relocatable: bool,
+    // skeleton units whose .dwo file couldn't be read
+    missing_dwos: usize,
+    // files whose tags came out of --cache-dir
+    cache_hits: usize,
+    // warnings printed, for --exit-code-on-warnings
+    warnings: Vec<String>,
+}
+impl Stats {
+    // Print a warning on stderr and keep it
+    fn warn(&mut self, msg: &str) {
+        self.warnings.push(msg.to_string());
+        eprintln!("warning: {}", msg);
+    }
+    fn add(&mut self, other: &Stats) {
+        self.units += other.units;
+        for (version, count) in other.unit_versions.iter() {
+            *self.unit_versions.entry(*version).or_insert(0) += count;
+        }
+        self.subprograms += other.subprograms;
+        self.tags += other.tags;
+        self.relocatable |= other.relocatable;
+        self.missing_dwos += other.missing_dwos;
+        self.cache_hits += other.cache_hits;
+        self.warnings.extend(other.warnings.iter().cloned());
+    }
+}
+fn print_stats(stats: &Stats) {
+    if stats.relocatable {
+        eprintln!("relocatable object: addresses are section relative");
+    }
+    eprintln!("units: {}", stats.units);
+    for (version, count) in stats.unit_versions.iter() {
+        eprintln!("  dwarf {}: {}", version, count);
+    }
+    if stats.missing_dwos > 0 {
+        eprintln!("missing .dwo files: {}", stats.missing_dwos);
+    }
+    if stats.cache_hits > 0 {
+        eprintln!("read from the cache: {} file(s)", stats.cache_hits);
+    }
+    eprintln!("subprograms: {}", stats.subprograms);
+    eprintln!("tags: {}", stats.tags);
+}
+// Whether data starts with the header of a tar archive
+fn is_tar(data: &[u8]) -> bool {
+    data.get(257..262) == Some(b"ustar".as_slice())
+}
+// Call add with the name, "archive.tar(member.o)", and contents of each elf
+// file in a tar archive. The members are read one at a time, straight out of
+// the archive. Members that aren't elf files are skipped with a warning.
+fn read_tar_members(
+    path: &str,
+    reader: impl std::io::Read,
+    add: &mut impl FnMut(&str, &[u8], &mut Stats),
+    stats: &mut Stats,
+) {
+    let mut archive = tar::Archive::new(reader);
+    let entries = match archive.entries() {
+        Ok(entries) => entries,
+        Err(e) => {
+            stats.warn(&format!("could not read {}: {}", path, e));
+            return;
+        }
+    };
+    for entry in entries {
+        let mut entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                stats.warn(&format!(
+                    "could not read {}: {}, the rest of it is left out",
+                    path, e
+                ));
+                return;
+            }
+        };
+        // directories, links and the like
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let name = match entry.path() {
+            Ok(member) => format!("{}({})", path, member.display()),
+            Err(_) => format!("{}(?)", path),
+        };
+        let mut data = Vec::new();
+        if let Err(e) = std::io::Read::read_to_end(&mut entry, &mut data) {
+            stats.warn(&format!(
+                "could not read {}: {}, the rest of the archive is left out",
+                name, e
+            ));
+            return;
+        }
+        if !data.starts_with(b"\x7fELF") {
+            stats.warn(&format!("{} isn't an elf file, skipping it", name));
+            continue;
+        }
+        add(&name, &data, stats);
+    }
+}
+// If data is a tar archive, gzip compressed or not, call add for each of its
+// elf files and return true
+fn read_archive(
+    path: &str,
+    data: &[u8],
+    add: &mut impl FnMut(&str, &[u8], &mut Stats),
+    stats: &mut Stats,
+) -> bool {
+    if data.starts_with(&[0x1f, 0x8b]) {
+        // only the first header is decompressed to see what is inside
+        let mut first = [0; 512];
+        let mut decoder = flate2::read::GzDecoder::new(data);
+        if std::io::Read::read_exact(&mut decoder, &mut first).is_err() || !is_tar(&first) {
+            return false;
+        }
+        read_tar_members(path, flate2::read::GzDecoder::new(data), add, stats);
+        return true;
+    }
+    if is_tar(data) {
+        read_tar_members(path, data, add, stats);
+        return true;
+    }
+    false
+}
+// Read the tags of one elf file, from --cache-dir if it was read before
+#[allow(clippy::too_many_arguments)]
+fn read_input<W: Write>(
+    path: &str,
+    file_data: &[u8],
+    options: &Options,
+    profile: &mut Profile,
+    stats: &mut Stats,
+    stream: &mut Option<TagStream<W>>,
+    wanted: Option<&[String]>,
+    matched: &mut [bool],
+) -> (Vec<FunctionInfo>, Provenance) {
+    // the cache holds what was read before any filtering, --unsorted writes
+    // the tags before they could be stored
+    let start = profile.start();
+    let cache_path = match (&options.cache_dir, &stream) {
+        (Some(dir), None) => Some(cache_path(dir, file_data, options)),
+        _ => None,
+    };
+    let cached = cache_path.as_ref().and_then(|p| read_cache(p, path));
+    profile.record("cache", start);
+    match cached {
+        Some((list, provenance, file_stats)) => {
+            stats.add(&file_stats);
+            stats.cache_hits += 1;
+            (list, provenance)
+        }
+        None => {
+            let mut file_stats = Stats::default();
+            let (list, provenance, dwo_files) = read_tags(
+                path,
+                file_data,
+                options,
+                profile,
+                &mut file_stats,
+                stream,
+                wanted,
+                matched,
+            );
+            stats.add(&file_stats);
+            // a file that gave warnings isn't stored, so they are printed
+            // again the next time
+            let clean = file_stats.warnings.is_empty();
+            if let (Some(cache_path), true) = (&cache_path, clean) {
+                let start = profile.start();
+                let result = write_cache(cache_path, &list, &provenance, &file_stats, &dwo_files);
+                if let Err(e) = result {
+                    stats.warn(&format!("could not write {}: {}", cache_path.display(), e));
+                }
+                profile.record("cache", start);
+            }
+            (list, provenance)
+        }
+    }
+}
+// Read the tags of one file, or of each elf file in a tar archive. With a
+// stream each unit's tags are written as soon as it has been walked and only
+// what is left is returned, otherwise they are all returned sorted and
+// deduplicated.
+fn extract_tags<W: Write>(
+    path: &str,
+    options: &Options,
+    profile: &mut Profile,
+    stats: &mut Stats,
+    stream: &mut Option<TagStream<W>>,
+) -> (Vec<FunctionInfo>, Provenance) {
+    let start = profile.start();
+    // an input that can't be read gives no tags, and a warning
+    let file_data = match fs::read(path) {
+        Ok(data) => Some(data),
+        Err(e) => {
+            stats.warn(&format!("could not read {}: {}, it is left out", path, e));
+            None
+        }
+    };
+    profile.record("read file", start);
+    extract_data_tags(
+        path,
+        file_data.as_deref(),
+        None,
+        options,
+        profile,
+        stats,
+        stream,
+    )
+}
+// The same for an input already read, of the given format or of the one its
+// first bytes show
+fn extract_data_tags<W: Write>(
+    path: &str,
+    file_data: Option<&[u8]>,
+    format: Option<InputFormat>,
+    options: &Options,
+    profile: &mut Profile,
+    stats: &mut Stats,
+    stream: &mut Option<TagStream<W>>,
+) -> (Vec<FunctionInfo>, Provenance) {
+    let wanted = options.include_only_files.as_ref().map(|(_, files)| files);
+    let mut matched = vec![false; wanted.map_or(0, |w| w.len())];
+    let mut file_info_list = Vec::new();
+    // the build-id and compilation directory of an archive are those of the
+    // first member that has them
+    let mut provenance = Provenance {
+        input_file: path.to_string(),
+        build_id: None,
+        comp_dir: None,
+    };
+    let mut add = |name: &str, data: &[u8], stats: &mut Stats| {
+        let (list, input) = read_input(
+            name,
+            data,
+            options,
+            profile,
+            stats,
+            stream,
+            wanted.map(|w| w.as_slice()),
+            &mut matched,
+        );
+        file_info_list.extend(list);
+        provenance.build_id = provenance.build_id.take().or(input.build_id);
+        provenance.comp_dir = provenance.comp_dir.take().or(input.comp_dir);
+    };
+    match (file_data, format) {
+        (None, _) => {}
+        (Some(file_data), Some(InputFormat::Elf)) => add(path, file_data, stats),
+        (Some(file_data), Some(InputFormat::Tar)) => {
+            if !read_archive(path, file_data, &mut add, stats) {
+                stats.warn(&format!("{} isn't a tar archive, it is left out", path));
+            }
+        }
+        (Some(file_data), None) => {
+            if !read_archive(path, file_data, &mut add, stats) {
+                add(path, file_data, stats);
+            }
+        }
+    }
+
+    let start = profile.start();
+    filter_tags(
+        &mut file_info_list,
+        options,
+        wanted.map(|w| w.as_slice()),
+        &mut matched,
+    );
+    if let Some((manifest, wanted)) = &options.include_only_files {
+        for (file, _) in wanted.iter().zip(&matched).filter(|(_, m)| !**m) {
+            stats.warn(&format!("{} from {} matched no tags", file, manifest));
+        }
+    }
+    if stream.is_none() {
+        // sort the ctags
+        file_info_list.sort();
+        if options.collation == Collation::Fold {
+            // stable, so repeated tags stay next to each other
+            file_info_list.sort_by_cached_key(|f| collation_key(&f.func_name));
+        }
+        // remove duplicates
+        let removed = dedup_tags(&mut file_info_list);
+        profile.record("sort", start);
+        if let Some(report) = &options.dedup_report {
+            if let Err(e) = write_dedup_report(report, &removed, options) {
+                eprintln!("error: could not write {}: {}", report, e);
+                std::process::exit(EXIT_FAILURE);
+            }
+        }
+    }
+    (file_info_list, provenance)
+}
+// Remove the repeats of a sorted list of tags, returning what was removed
+fn dedup_tags(file_info_list: &mut Vec<FunctionInfo>) -> Vec<FunctionInfo> {
+    let mut kept: Vec<FunctionInfo> = Vec::with_capacity(file_info_list.len());
+    let mut removed = Vec::new();
+    for f in file_info_list.drain(..) {
+        if kept.last() == Some(&f) {
+            removed.push(f);
+        } else {
+            kept.push(f);
+        }
+    }
+    *file_info_list = kept;
+    removed
+}
+// --dedup-report: write the tags that were left out as repeats, in the
+// output format and without a header. A tag repeated n times is written n - 1
+// times.
+fn write_dedup_report(
+    path: &str,
+    removed: &[FunctionInfo],
+    options: &Options,
+) -> std::io::Result<()> {
+    let mut out = std::io::BufWriter::new(fs::File::create(path)?);
+    if options.format == Format::Outline {
+        return write_outline(&mut out, removed, options).and_then(|_| out.flush());
+    }
+    let mut sources = SourceCache::default();
+    let lines: Vec<String> = removed
+        .iter()
+        .map(|f| format_file_info(f, &mut sources, options))
+        .collect();
+    write_tags(&mut out, &[], &lines)
+}
+// Walk all the units of a file. Also returns the .dwo files that were read.
+#[allow(clippy::too_many_arguments)]
+fn read_tags<W: Write>(
+    path: &str,
+    file_data: &[u8],
+    options: &Options,
+    profile: &mut Profile,
+    stats: &mut Stats,
+    stream: &mut Option<TagStream<W>>,
+    wanted: Option<&[String]>,
+    matched: &mut [bool],
+) -> (Vec<FunctionInfo>, Provenance, Vec<std::path::PathBuf>) {
+    let start = profile.start();
+    let slice = file_data;
+    // Get the Elf file
+    let file = match ElfBytes::<'_, elf::endian::AnyEndian>::minimal_parse(slice) {
+        Ok(file) => file,
+        Err(e) => {
+            stats.warn(&format!("could not parse {}: {}, it is left out", path, e));
+            let provenance = Provenance {
+                input_file: path.to_string(),
+                build_id: None,
+                comp_dir: None,
+            };
+            return (Vec::new(), provenance, Vec::new());
+        }
+    };
+    let file = &file;
+    let endian = match file.ehdr.endianness {
+        elf::endian::AnyEndian::Little => gimli::RunTimeEndian::Little,
+        elf::endian::AnyEndian::Big => gimli::RunTimeEndian::Big,
+    };
+    // load will request each required setion from load_section, relocated
+    // sections are owned so they are kept here while the dwarf borrows them
+    let dwarf_sections =
+        gimli::DwarfSections::load(|id| load_file_section(id, file, stats)).unwrap();
+    let mut dwarf = dwarf_sections.borrow(|section| gimli::EndianSlice::new(section, endian));
+    let sup_data = read_sup_file(path, file, options, stats);
+    let sup_file = sup_data.as_ref().and_then(|(sup_path, data, build_id)| {
+        let sup = match ElfBytes::<'_, elf::endian::AnyEndian>::minimal_parse(data) {
+            Ok(sup) => sup,
+            Err(e) => {
+                stats.warn(&format!("could not parse {}: {}", sup_path.display(), e));
+                return None;
+            }
+        };
+        if build_id.is_some() && read_build_id(&sup) != *build_id {
+            stats.warn(&format!(
+                "{} isn't the supplementary file .gnu_debugaltlink asks for, its build-id is different",
+                sup_path.display()
+            ));
+            return None;
+        }
+        Some(sup)
+    });
+    let sup_sections = sup_file
+        .as_ref()
+        .map(|sup| gimli::DwarfSections::load(|id| load_file_section(id, sup, stats)).unwrap());
+    if let (Some(sup), Some(sections)) = (&sup_file, &sup_sections) {
+        let endian = match sup.ehdr.endianness {
+            elf::endian::AnyEndian::Little => gimli::RunTimeEndian::Little,
+            elf::endian::AnyEndian::Big => gimli::RunTimeEndian::Big,
+        };
+        dwarf.set_sup(sections.borrow(|section| gimli::EndianSlice::new(section, endian)));
+    }
+    let debug_macro = load_file_section(gimli::SectionId::DebugMacro, file, stats).unwrap();
+    let debug_macinfo = load_file_section(gimli::SectionId::DebugMacinfo, file, stats).unwrap();
+    let macro_sections = MacroSections {
+        debug_macro: gimli::EndianSlice::new(&debug_macro, endian),
+        debug_macinfo: gimli::EndianSlice::new(&debug_macinfo, endian),
+        follow_imports: file.ehdr.e_type != elf::abi::ET_REL,
+    };
+    profile.record("load sections", start);
+    let mut iter = dwarf.units();
+    let mut other_units = OtherUnits::new(&dwarf, options.group_anonymous_by_file);
+    let mut demangled = Demangled::new();
+    let mut file_info_list: Vec<FunctionInfo> = Vec::new();
+    let symbols = read_symbols(file);
+    let mut provenance = Provenance {
+        input_file: path.to_string(),
+        build_id: read_build_id(file),
+        comp_dir: None,
+    };
+    stats.relocatable |= file.ehdr.e_type == elf::abi::ET_REL;
+    let mut dwo_files = Vec::new();
+    if let (Some((sup_path, _, _)), Some(_)) = (&sup_data, &sup_sections) {
+        // --cache-dir checks it for changes the same way as a .dwo file
+        dwo_files.push(sup_path.clone());
+    }
+    loop {
+        let start = profile.start();
+        // a section cut off part way, e.g. by an interrupted build or
+        // download, still gives the tags of the units before the cut
+        let header = match iter.next() {
+            Ok(Some(header)) => header,
+            Ok(None) => break,
+            Err(e) => {
+                stats.warn(&format!(
+                    ".debug_info is cut off or damaged after {} unit(s) ({}), the rest of it is left out",
+                    stats.units, e
+                ));
+                break;
+            }
+        };
+        // The rest is left out with a warning, which also keeps the partial
+        // tags out of --cache-dir
+        if options.max_units == Some(stats.units) {
+            stats.warn(&format!(
+                "stopped after --max-units {} unit(s), the tags of the rest of {} are left out",
+                stats.units, path
+            ));
+            break;
+        }
+        stats.units += 1;
+        *stats.unit_versions.entry(header.version()).or_insert(0) += 1;
+        let unit = match dwarf.unit(header) {
+            Ok(unit) => unit,
+            Err(e) => {
+                stats.warn(&format!(
+                    "could not read the unit at 0x{:x}: {}, it is left out",
+                    header.offset().as_debug_info_offset().map_or(0, |o| o.0),
+                    e
+                ));
+                continue;
+            }
+        };
+        profile.record("parse units", start);
+        if provenance.comp_dir.is_none() {
+            provenance.comp_dir = unit
+                .comp_dir
+                .and_then(|d| d.to_string().ok().map(|d| d.to_string()));
+        }
+        let start = profile.start();
+        let unit_info = unit_info(&dwarf, &unit);
+        profile.record("resolve files", start);
+        // A skeleton unit only points at the .dwo file holding its DIEs
+        if let Some(dwo_name) = unit_dwo_name(&dwarf, &unit) {
+            let start = profile.start();
+            match process_dwo(
+                &dwarf,
+                &unit,
+                &unit_info,
+                &dwo_name,
+                &symbols,
+                options,
+                stats,
+                &mut file_info_list,
+                &mut demangled,
+            ) {
+                Ok(dwo_path) => dwo_files.push(dwo_path),
+                Err(msg) => {
+                    stats.warn(&format!("{}, its tags are left out", msg));
+                    stats.missing_dwos += 1;
+                }
+            }
+            profile.record("walk DIEs", start);
+        } else {
+            // Iterate over all of this compilation unit's entries
+            let start = profile.start();
+            process_unit(
+                &dwarf,
+                &unit,
+                &unit_info,
+                &symbols,
+                options,
+                stats,
+                &mut file_info_list,
+                &mut other_units,
+                &mut demangled,
+            );
+            if unit_kinds(unit_info.language, options).contains(&Kind::Macro) {
+                process_macros(
+                    &dwarf,
+                    &unit,
+                    &unit_info,
+                    &macro_sections,
+                    options,
+                    &mut file_info_list,
+                    stats,
+                );
+            }
+            profile.record("walk DIEs", start);
+        }
+        if let Some(stream) = stream {
+            let start = profile.start();
+            filter_tags(&mut file_info_list, options, wanted, matched);
+            let header = pseudo_tags(options, &provenance);
+            stream
+                .write(&header, &file_info_list, options)
+                .expect("Should have been able to write the tags");
+            file_info_list.clear();
+            profile.record("write", start);
+        }
+    }
+    if stats.missing_dwos > 0 {
+        stats.warn(&format!(
+            "{} .dwo file(s) could not be read, try --dwo-dir",
+            stats.missing_dwos
+        ));
+    }
+    (file_info_list, provenance, dwo_files)
+}
+// Write the tags of options.path the way the options ask for. Returns the
+// source files that couldn't be read for the tags.
+fn write_tag_output(
+    options: &Options,
+    profile: &mut Profile,
+    stats: &mut Stats,
+) -> BTreeSet<String> {
+    let mut stream = options
+        .unsorted
+        .then(|| TagStream::new(std::io::BufWriter::new(std::io::stdout().lock()), options));
+    let (file_info_list, provenance) =
+        extract_tags(&options.path, options, profile, stats, &mut stream);
+    let start = profile.start();
+    let header = pseudo_tags(options, &provenance);
+    let mut sources = SourceCache::default();
+    if let Some(stream) = &mut stream {
+        stream
+            .finish(&header)
+            .expect("Should have been able to write the tags");
+    } else if let Some(target) = &options.merge_into {
+        if let Err(e) = merge_into(target, &file_info_list, &header, options, &mut sources) {
+            eprintln!("error: could not update {}: {}", target, e);
+            std::process::exit(EXIT_FAILURE);
+        }
+        if options.watch_sources {
+            // watching doesn't end, fail before it starts
+            check_missing_sources(options, &sources.missing);
+            if let Err(e) = watch_sources(target, &file_info_list, &header, options) {
+                eprintln!("error: {}", e);
+                std::process::exit(EXIT_FAILURE);
+            }
+        }
+    } else if let Some(out_dir) = &options.group_by_directory {
+        if let Err(e) =
+            write_directory_groups(out_dir, &file_info_list, &header, options, &mut sources)
+        {
+            eprintln!("error: could not write the tags to {}: {}", out_dir, e);
+            std::process::exit(EXIT_FAILURE);
+        }
+    } else if options.format == Format::Outline {
+        let stdout = std::io::stdout();
+        let mut out = std::io::BufWriter::new(stdout.lock());
+        write_outline(&mut out, &file_info_list, options)
+            .expect("Should have been able to write the outline");
+    } else {
+        let lines: Vec<String> = file_info_list
+            .iter()
+            .map(|f| format_file_info(f, &mut sources, options))
+            .collect();
+        let stdout = std::io::stdout();
+        let mut out = std::io::BufWriter::new(stdout.lock());
+        write_tags(&mut out, &header, &lines).expect("Should have been able to write the tags");
+    }
+    profile.record("write", start);
+    stats.tags = match &stream {
+        Some(stream) => stream.tags,
+        None => file_info_list.len(),
+    };
+    match stream {
+        Some(stream) => stream.sources.missing,
+        None => sources.missing,
+    }
+}
+// What a symbol is matched on between two builds: its name, scope and kind
+type SymbolKey = (String, Option<(Kind, String)>, Kind);
+// Where a symbol was declared, as (file, line), usually just once but e.g.
+// static functions of the same name can be in several files
+type Locations = BTreeSet<(String, u64)>;
+fn symbol_locations(list: &[FunctionInfo]) -> BTreeMap<SymbolKey, Locations> {
+    let mut symbols: BTreeMap<SymbolKey, Locations> = BTreeMap::new();
+    for f in list.iter() {
+        symbols
+            .entry((f.func_name.clone(), f.scope.clone(), f.kind))
+            .or_default()
+            .insert((f.file_name.clone(), f.line_number));
+    }
+    symbols
+}
+// The symbols only in the new build, only in the old one, and those in both
+// whose locations differ, each sorted by name
+#[derive(Default)]
+struct SymbolDiff {
+    added: Vec<(SymbolKey, Locations)>,
+    removed: Vec<(SymbolKey, Locations)>,
+    moved: Vec<(SymbolKey, Locations, Locations)>,
+}
+fn compare_tags(old: &[FunctionInfo], new: &[FunctionInfo]) -> SymbolDiff {
+    let old = symbol_locations(old);
+    let mut new = symbol_locations(new);
+    let mut diff = SymbolDiff::default();
+    for (key, old_locations) in old.into_iter() {
+        match new.remove(&key) {
+            None => diff.removed.push((key, old_locations)),
+            Some(new_locations) if new_locations != old_locations => {
+                diff.moved.push((key, old_locations, new_locations))
+            }
+            Some(_) => {}
+        }
+    }
+    diff.added = new.into_iter().collect();
+    diff
+}
+// "add (function)" or "Put (subprogram, package:Pkg)"
+fn symbol_description(key: &SymbolKey) -> String {
+    let (name, scope, kind) = key;
+    match scope {
+        Some((scope_kind, scope)) => format!(
+            "{} ({}, {}:{})",
+            name,
+            kind.long_name(),
+            scope_kind.long_name(),
+            scope
+        ),
+        None => format!("{} ({})", name, kind.long_name()),
+    }
+}
+fn text_locations(locations: &Locations) -> String {
+    let locations: Vec<String> = locations
+        .iter()
+        .map(|(file, line)| format!("{}:{}", file, line))
+        .collect();
+    locations.join(", ")
+}
+fn write_diff_text(out: &mut impl Write, diff: &SymbolDiff) -> std::io::Result<()> {
+    for (key, locations) in diff.added.iter() {
+        writeln!(
+            out,
+            "+ {}\t{}",
+            symbol_description(key),
+            text_locations(locations)
+        )?;
+    }
+    for (key, locations) in diff.removed.iter() {
+        writeln!(
+            out,
+            "- {}\t{}",
+            symbol_description(key),
+            text_locations(locations)
+        )?;
+    }
+    for (key, old, new) in diff.moved.iter() {
+        writeln!(
+            out,
+            "~ {}\t{} -> {}",
+            symbol_description(key),
+            text_locations(old),
+            text_locations(new)
+        )?;
+    }
+    writeln!(
+        out,
+        "{} added, {} removed, {} moved",
+        diff.added.len(),
+        diff.removed.len(),
+        diff.moved.len()
+    )?;
+    out.flush()
+}
+fn json_string(s: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            c if (c as u32) < 0x20 => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+fn json_symbol(key: &SymbolKey) -> String {
+    let (name, scope, kind) = key;
+    let mut object = format!(
+        "\"name\": {}, \"kind\": {}",
+        json_string(name),
+        json_string(kind.long_name())
+    );
+    if let Some((scope_kind, scope)) = scope {
+        object.push_str(&format!(
+            ", \"scope\": {}",
+            json_string(&format!("{}:{}", scope_kind.long_name(), scope))
+        ));
+    }
+    object
+}
+fn json_locations(locations: &Locations) -> String {
+    let locations: Vec<String> = locations
+        .iter()
+        .map(|(file, line)| format!("{{\"file\": {}, \"line\": {}}}", json_string(file), line))
+        .collect();
+    format!("[{}]", locations.join(", "))
+}
+fn write_diff_json(out: &mut impl Write, diff: &SymbolDiff) -> std::io::Result<()> {
+    let added: Vec<String> = diff
+        .added
+        .iter()
+        .map(|(key, l)| {
+            format!(
+                "{{{}, \"locations\": {}}}",
+                json_symbol(key),
+                json_locations(l)
+            )
+        })
+        .collect();
+    let removed: Vec<String> = diff
+        .removed
+        .iter()
+        .map(|(key, l)| {
+            format!(
+                "{{{}, \"locations\": {}}}",
+                json_symbol(key),
+                json_locations(l)
+            )
+        })
+        .collect();
+    let moved: Vec<String> = diff
+        .moved
+        .iter()
+        .map(|(key, old, new)| {
+            format!(
+                "{{{}, \"old\": {}, \"new\": {}}}",
+                json_symbol(key),
+                json_locations(old),
+                json_locations(new)
+            )
+        })
+        .collect();
+    writeln!(out, "{{")?;
+    for (i, (name, entries)) in [("added", added), ("removed", removed), ("moved", moved)]
+        .iter()
+        .enumerate()
+    {
+        let separator = if i < 2 { "," } else { "" };
+        if entries.is_empty() {
+            writeln!(out, "  \"{}\": []{}", name, separator)?;
+        } else {
+            writeln!(
+                out,
+                "  \"{}\": [\n    {}\n  ]{}",
+                name,
+                entries.join(",\n    "),
+                separator
+            )?;
+        }
+    }
+    writeln!(out, "}}")?;
+    out.flush()
+}
+// The compare subcommand: read the tags of both files with the same options
+// and write which symbols were added, removed or moved
+fn write_comparison(options: &Options, new_path: &str, profile: &mut Profile, stats: &mut Stats) {
+    let mut no_stream: Option<TagStream<std::io::Sink>> = None;
+    let (old, _) = extract_tags(&options.path, options, profile, stats, &mut no_stream);
+    let (new, _) = extract_tags(new_path, options, profile, stats, &mut no_stream);
+    let start = profile.start();
+    let diff = compare_tags(&old, &new);
+    let stdout = std::io::stdout();
+    let mut out = std::io::BufWriter::new(stdout.lock());
+    match options.format {
+        Format::Json => write_diff_json(&mut out, &diff),
+        _ => write_diff_text(&mut out, &diff),
+    }
+    .expect("Should have been able to write the comparison");
+    profile.record("write", start);
+    stats.tags = old.len() + new.len();
+}
+// A pseudo tag of the files read by merge: its value, its line and the file
+// it was first found in. None once two files gave different values.
+type MergedPseudoTag = Option<(String, String, String)>;
+// Read the tags files in dir, in name order, into their tag lines and the
+// pseudo tags they agree on. Only the files directly in dir are read.
+fn read_tags_dir(dir: &str, stats: &mut Stats) -> (Vec<String>, Vec<(String, MergedPseudoTag)>) {
+    let mut paths: Vec<std::path::PathBuf> = match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().is_ok_and(|t| t.is_file()))
+            .map(|entry| entry.path())
+            .collect(),
+        Err(e) => {
+            eprintln!("error: could not read {}: {}", dir, e);
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+    paths.sort();
+    let mut lines = Vec::new();
+    let mut pseudo_tags: Vec<(String, MergedPseudoTag)> = Vec::new();
+    for path in paths.iter() {
+        let name = path.display().to_string();
+        let data = match fs::read(path) {
+            Ok(data) => data,
+            Err(e) => {
+                stats.warn(&format!("could not read {}: {}, it is left out", name, e));
+                continue;
+            }
+        };
+        // the file may start with a BOM, from --output-bom or an editor
+        let data = data.strip_prefix(UTF8_BOM).unwrap_or(&data);
+        let Ok(text) = std::str::from_utf8(data) else {
+            stats.warn(&format!("{} is not UTF-8, it is left out", name));
+            continue;
+        };
+        let encoding = text
+            .lines()
+            .find_map(|line| line.strip_prefix("!_TAG_FILE_ENCODING\t"))
+            .and_then(|rest| rest.split('\t').next())
+            .filter(|e| !e.eq_ignore_ascii_case("utf-8") && !e.eq_ignore_ascii_case("utf8"));
+        if let Some(encoding) = encoding {
+            stats.warn(&format!(
+                "{} is encoded in {}, only UTF-8 tags files are merged, it is left out",
+                name, encoding
+            ));
+            continue;
+        }
+        for line in text.lines().filter(|line| !line.is_empty()) {
+            let Some(pseudo_tag) = line.strip_prefix("!_TAG_") else {
+                lines.push(line.to_string());
+                continue;
+            };
+            let mut parts = pseudo_tag.split('\t');
+            let tag = parts.next().unwrap_or("");
+            let value = parts.next().unwrap_or("");
+            match tag {
+                // the merged file has a header of its own
+                "FILE_SORTED" | "FILE_ENCODING" => {}
+                "FILE_FORMAT" if value != "2" => stats.warn(&format!(
+                    "{} is a format {} tags file, its lines are merged as they are",
+                    name, value
+                )),
+                "FILE_FORMAT" => {}
+                _ => match pseudo_tags.iter_mut().find(|(t, _)| t == tag) {
+                    None => pseudo_tags.push((
+                        tag.to_string(),
+                        Some((value.to_string(), line.to_string(), name.clone())),
+                    )),
+                    Some((_, merged)) => {
+                        if let Some((first_value, _, first_file)) = merged {
+                            if first_value != value {
+                                stats.warn(&format!(
+                                    "{} and {} give different values for !_TAG_{}, it is left out",
+                                    first_file, name, tag
+                                ));
+                                *merged = None;
+                            }
+                        }
+                    }
+                },
+            }
+        }
+    }
+    (lines, pseudo_tags)
+}
+// The merge subcommand: combine the tags files in a directory, e.g. written
+// by separate runs over the objects of a build, into one. The lines are
+// sorted and deduplicated as --merge-into does, no DWARF is read.
+fn write_merged_dir(
+    out: &mut impl Write,
+    options: &Options,
+    profile: &mut Profile,
+    stats: &mut Stats,
+) {
+    let start = profile.start();
+    let (mut lines, pseudo_tags) = read_tags_dir(&options.path, stats);
+    profile.record("read file", start);
+    let start = profile.start();
+    let removed = sort_tag_lines(&mut lines, options.collation);
+    profile.record("sort", start);
+    if let Some(report) = &options.dedup_report {
+        let result = fs::File::create(report)
+            .and_then(|file| write_tags(&mut std::io::BufWriter::new(file), &[], &removed));
+        if let Err(e) = result {
+            eprintln!("error: could not write {}: {}", report, e);
+            std::process::exit(EXIT_FAILURE);
+        }
+    }
+    let start = profile.start();
+    let mut header: Vec<String> = Vec::new();
+    if !options.no_header {
+        header.extend(TAG_HEADER.iter().map(|l| l.to_string()));
+    }
+    if !options.no_header && options.collation == Collation::Fold {
+        header[1] = TAG_FILE_UNSORTED.to_string();
+    }
+    if !options.no_header && !options.no_pseudo_tags {
+        header.extend(pseudo_tags.into_iter().filter_map(|(_, m)| Some(m?.1)));
+    }
+    write_tags(out, &header, &lines).expect("Should have been able to write the tags");
+    profile.record("write", start);
+    stats.tags = lines.len();
+}
+// What extract_tags_with_format is given
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum InputFormat {
+    Elf,
+    // a tar archive of elf files, gzip compressed or not
+    Tar,
+}
+// The tag lines of an elf file or tar archive that is already in memory, as
+// dwarf-easy writes them with the default options, without the header. The
+// format is taken as given instead of from the first bytes; the byte order
+// of an elf file always comes from its header. name is what warnings call it.
+pub fn extract_tags_with_format(name: &str, data: &[u8], format: InputFormat) -> Vec<String> {
+    let options = parse_args(std::iter::once(name.to_string()));
+    let mut profile = Profile::new(false);
+    let mut stats = Stats::default();
+    let mut no_stream: Option<TagStream<std::io::Sink>> = None;
+    let (list, _) = extract_data_tags(
+        name,
+        Some(data),
+        Some(format),
+        &options,
+        &mut profile,
+        &mut stats,
+        &mut no_stream,
+    );
+    let mut sources = SourceCache::default();
+    list.iter()
+        .map(|f| format_file_info(f, &mut sources, &options))
+        .collect()
+}
+// The dwarf-easy command, with the arguments it was run with
+pub fn run() {
+    let options = parse_args(std::env::args().skip(1));
+    let mut profile = Profile::new(options.profile);
+    let mut stats = Stats::default();
+    // compare and merge don't read sources
+    let missing_sources = match &options.compare_with {
+        Some(new_path) => {
+            write_comparison(&options, new_path, &mut profile, &mut stats);
+            BTreeSet::new()
+        }
+        None if options.merge_dir => {
+            let stdout = std::io::stdout();
+            let mut out = std::io::BufWriter::new(stdout.lock());
+            write_merged_dir(&mut out, &options, &mut profile, &mut stats);
+            BTreeSet::new()
+        }
+        None => write_tag_output(&options, &mut profile, &mut stats),
+    };
+    if options.profile {
+        profile.print();
+    }
+    if options.stats {
+        print_stats(&stats);
+    }
+    if let Err(msg) = check_empty(&options, &mut stats) {
+        eprintln!("error: {}", msg);
+        std::process::exit(EXIT_FAILURE);
+    }
+    // only once the tags are written, so a strict run still leaves them
+    check_missing_sources(&options, &missing_sources);
+    if let Err((msg, code)) = check_warnings(&options, &stats) {
+        eprintln!("error: {}", msg);
+        std::process::exit(code);
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A file in tests/fixtures, build.sh there makes them
+    fn fixture(name: &str) -> String {
+        format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name)
+    }
+    // The options of a command line that reads the fixture input
+    fn fixture_options(args: &[&str], input: &str) -> Options {
+        let args = args.iter().map(|a| a.to_string());
+        parse_args(args.chain(std::iter::once(fixture(input))))
+    }
+    // An empty directory of its own for a test to write in
+    fn temp_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("dwarf-easy-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+    fn read_fixture(options: &Options, stats: &mut Stats) -> Vec<FunctionInfo> {
+        let mut stream = None::<TagStream<std::io::Sink>>;
+        extract_tags(
+            &options.path,
+            options,
+            &mut Profile::new(false),
+            stats,
+            &mut stream,
+        )
+        .0
+    }
+    fn function(name: &str, file: &str, line: u64) -> FunctionInfo {
+        FunctionInfo {
+            func_name: name.to_string(),
+            file_name: file.to_string(),
+            line_number: line,
+            column_number: 0,
+            kind: Kind::Function,
+            scope: None,
+            fields: Vec::new(),
+            comp_dir: None,
+        }
+    }
+    // For the header of tags read without --emit-provenance
+    fn no_provenance() -> Provenance {
+        Provenance {
+            input_file: String::new(),
+            build_id: None,
+            comp_dir: None,
+        }
+    }
+    fn format_tags(list: &[FunctionInfo], options: &Options) -> Vec<String> {
+        let mut sources = SourceCache::default();
+        list.iter()
+            .map(|f| format_file_info(f, &mut sources, options))
+            .collect()
+    }
+    // The tag lines written for a fixture, without the pseudo tags
+    fn tag_lines(args: &[&str], input: &str) -> Vec<String> {
+        tag_lines_and_warnings(args, input).0
+    }
+    // The same and the number of warnings reading it printed
+    fn tag_lines_and_warnings(args: &[&str], input: &str) -> (Vec<String>, usize) {
+        let options = fixture_options(args, input);
+        let mut stats = Stats::default();
+        let list = read_fixture(&options, &mut stats);
+        (format_tags(&list, &options), stats.warnings.len())
+    }
+
+    #[test]
+    fn follow_origin_for_location() {
+        // the instance says line 7, its abstract origin line 2
+        assert_eq!(
+            tag_lines(&[], "origin.o"),
+            ["twice\torigin.c\t:2;\"\tf", "twice\torigin.c\t:7;\"\tf"]
+        );
+        assert_eq!(
+            tag_lines(&["--follow-origin-for-location"], "origin.o"),
+            ["twice\torigin.c\t:2;\"\tf"]
+        );
+    }
+
+    #[test]
+    fn merge_replaces_the_tags_of_retagged_files() {
+        let existing = "!_TAG_FILE_SORTED\t1\t/0=unsorted/\n\
+                        a\ta.c\t:1;\"\tf\n\
+                        b\tb.c\t:1;\"\tf\n\
+                        c\ta.c\t:9;\"\tf\n";
+        let new_lines = ["aa\ta.c\t:2;\"\tf".to_string()];
+        assert_eq!(
+            merge_tag_lines(existing, &new_lines, &["a.c"], Collation::Byte),
+            ["aa\ta.c\t:2;\"\tf", "b\tb.c\t:1;\"\tf"]
+        );
+    }
+
+    #[test]
+    fn interrupted_merge_leaves_the_tags_file() {
+        let dir = temp_dir("interrupted-merge");
+        let path = dir.join("tags");
+        fs::write(&path, "a\ta.c\t:1;\"\tf\n").unwrap();
+        let result = replace_file(path.to_str().unwrap(), |out| {
+            out.write_all(b"b\tb.c\t:1;\"\tf\n")?;
+            Err(std::io::Error::other("interrupted"))
+        });
+        assert!(result.is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "a\ta.c\t:1;\"\tf\n");
+        let left: Vec<_> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name())
+            .collect();
+        assert_eq!(left, ["tags"]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn ada_names() {
+        assert_eq!(
+            split_ada_name("pkg__child__do_it", true, "."),
+            ("Do_It".to_string(), Some("Pkg.Child".to_string()))
+        );
+        assert_eq!(
+            split_ada_name("pkg__child__do_it", false, "."),
+            (
+                "pkg__child__do_it".to_string(),
+                Some("pkg__child".to_string())
+            )
+        );
+        assert_eq!(
+            split_ada_name("_ada_main", true, "."),
+            ("Main".to_string(), None)
+        );
+        // not a flattened name, so nothing to split
+        assert_eq!(
+            split_ada_name("a____b", true, "."),
+            ("A____B".to_string(), None)
+        );
+        assert_eq!(
+            tag_lines(&["--ada-names"], "ada.o"),
+            [
+                "Do_It\tpkg-child.adb\t:3;\"\tr\tpackage:Pkg.Child",
+                "Main\tpkg-child.adb\t:10;\"\tr",
+            ]
+        );
+    }
+
+    #[test]
+    fn watched_tags_refresh_only_the_changed_files() {
+        let dir = temp_dir("watch");
+        let (a, b, target) = (dir.join("a.c"), dir.join("b.c"), dir.join("tags"));
+        fs::write(&a, "int a(void)\n").unwrap();
+        fs::write(&b, "int b(void)\n").unwrap();
+        fs::write(&target, "other\tother.c\t:1;\"\tf\n").unwrap();
+        let target = target.to_str().unwrap();
+        let (a_name, b_name) = (a.display().to_string(), b.display().to_string());
+        let list = [function("a", &a_name, 1), function("b", &b_name, 1)];
+        let options = fixture_options(&["--excmd", "pattern", "--merge-into", target], "reloc.o");
+        let mut tags = WatchedTags::new(&list, &options);
+        tags.refresh(&HashSet::new(), target, &[], &options)
+            .unwrap();
+        let expected = |a_line: &str, b_line: &str| {
+            format!(
+                "a\t{}\t/^{}$/;\"\tf\nb\t{}\t/^{}$/;\"\tf\nother\tother.c\t:1;\"\tf\n",
+                a_name, a_line, b_name, b_line
+            )
+        };
+        assert_eq!(
+            fs::read_to_string(target).unwrap(),
+            expected("int a(void)", "int b(void)")
+        );
+        // b.c changed as well, but no event said so
+        fs::write(&a, "int a(int x)\n").unwrap();
+        fs::write(&b, "int b(int y)\n").unwrap();
+        tags.refresh(&HashSet::from([a.clone()]), target, &[], &options)
+            .unwrap();
+        assert_eq!(
+            fs::read_to_string(target).unwrap(),
+            expected("int a(int x)", "int b(void)")
+        );
+        tags.refresh(&HashSet::from([b.clone()]), target, &[], &options)
+            .unwrap();
+        assert_eq!(
+            fs::read_to_string(target).unwrap(),
+            expected("int a(int x)", "int b(int y)")
+        );
+    }
+
+    #[test]
+    fn fields() {
+        let fields = |value, addresses| parse_fields(value, addresses, false, false);
+        assert_eq!(fields(None, false), ["namesrc", "visibility", "typeref"]);
+        assert_eq!(
+            fields(Some("+dwarftag,-visibility"), false),
+            ["namesrc", "typeref", "dwarftag"]
+        );
+        // a list without + or - replaces the defaults
+        assert_eq!(
+            fields(Some("typeref,+signature"), false),
+            ["typeref", "signature"]
+        );
+        assert_eq!(
+            fields(Some("-namesrc,,-typeref"), true),
+            ["visibility", "address"]
+        );
+        assert_eq!(
+            fields(Some("-address"), true),
+            ["namesrc", "visibility", "typeref", "address"]
+        );
+        assert_eq!(
+            tag_lines(&["--fields", "+dwarftag", "--kinds", "fN"], "imported.o"),
+            [
+                "d\timported.cpp\t:9;\"\tN\tdwarftag:DW_TAG_imported_declaration",
+                "depth\timported.cpp\t:4;\"\tf\tnamespace:lib::detail\tdwarftag:DW_TAG_subprogram",
+                "helper\timported.cpp\t:2;\"\tf\tnamespace:lib\tdwarftag:DW_TAG_subprogram",
+                "helper\timported.cpp\t:8;\"\tN\tdwarftag:DW_TAG_imported_declaration",
+                "run\timported.cpp\t:10;\"\tf\tnamespace:app\tdwarftag:DW_TAG_subprogram",
+            ]
+        );
+        assert_eq!(
+            tag_lines(
+                &["--fields", "+dwarftag,-typeref", "--kinds", "v"],
+                "arrays.o"
+            )[0],
+            "grid\tarrays.c\t:1;\"\tv\tdwarftag:DW_TAG_variable"
+        );
+    }
+
+    #[test]
+    fn list_kinds() {
+        assert_eq!(
+            kind_list(&BTreeMap::new()),
+            "C\n\
+             \tf\tfunction\ton\tfunctions and methods\n\
+             \tv\tvariable\toff\tglobal and namespace scope variables\n\
+             \td\tmacro\ton\t#define macros, only in builds with -g3\n\
+             C++\n\
+             \tf\tfunction\ton\tfunctions and methods\n\
+             \tN\tname\toff\tnames brought in by using declarations and namespace aliases\n\
+             \tv\tvariable\toff\tglobal and namespace scope variables\n\
+             \td\tmacro\ton\t#define macros, only in builds with -g3\n\
+             \tn\tnamespace\ton\tnamespaces, as a scope\n\
+             \tc\tclass\ton\tclasses, as a scope\n\
+             \ts\tstruct\ton\tstructs, as a scope\n\
+             \tu\tunion\ton\tunions, as a scope\n\
+             Ada\n\
+             \tr\tsubprogram\ton\tprocedures and functions\n\
+             \tv\tvariable\toff\tglobal and namespace scope variables\n\
+             \tp\tpackage\ton\tpackages, as a scope\n\
+             Go\n\
+             \tf\tfunction\ton\tfunctions and methods\n\
+             \tv\tvariable\toff\tglobal and namespace scope variables\n\
+             \tp\tpackage\ton\tpackages, as a scope\n\
+             \tt\ttype\ton\tmethod receiver types, as a scope\n"
+        );
+        // the letters are the ones --kind-map gives
+        let mapped = kind_list(&parse_kind_map("function=F,name=U"));
+        assert_eq!(
+            mapped,
+            kind_list(&BTreeMap::new())
+                .replace("\tf\t", "\tF\t")
+                .replace("\tN\t", "\tU\t")
+        );
+    }
+
+    #[test]
+    fn kind_map() {
+        let kind_map = parse_kind_map("function=F,subprogram=P");
+        assert_eq!(
+            kind_map.into_iter().collect::<Vec<_>>(),
+            [(Kind::Function, 'F'), (Kind::Subprogram, 'P')]
+        );
+        assert_eq!(
+            tag_lines(&["--kind-map", "subprogram=P"], "ada.o"),
+            [
+                "_ada_main\tpkg-child.adb\t:10;\"\tP",
+                "pkg__child__do_it\tpkg-child.adb\t:3;\"\tP\tpackage:pkg__child",
+            ]
+        );
+    }
+
+    #[test]
+    fn kinds_by_language() {
+        use Kind::*;
+        let no_map = BTreeMap::new();
+        assert_eq!(
+            parse_kinds(Some("fv"), &no_map),
+            (vec![Function, Variable], vec![])
+        );
+        assert_eq!(
+            parse_kinds(None, &no_map).0,
+            [Function, Subprogram, Package, Macro, Namespace, Class, Struct, Union, Type]
+        );
+        assert_eq!(
+            parse_kinds(Some("c:fvd,c++:fN,*:f"), &no_map),
+            (
+                vec![Function],
+                vec![
+                    ("c", vec![Function, Variable, Macro]),
+                    ("c++", vec![Function, Imported])
+                ]
+            )
+        );
+        // any case, cpp and cxx for c++, no * keeps the default kinds
+        let (fallback, by_language) = parse_kinds(Some("C:v,CPP:f,cxx:N,Rust:f"), &no_map);
+        assert_eq!(fallback, parse_kinds(None, &no_map).0);
+        assert_eq!(
+            by_language,
+            [
+                ("c", vec![Variable]),
+                ("c++", vec![Function]),
+                ("c++", vec![Imported]),
+                ("rust", vec![Function])
+            ]
+        );
+        // the letters are those after --kind-map
+        let kind_map = parse_kind_map("variable=V");
+        assert_eq!(
+            parse_kinds(Some("c:fV"), &kind_map).1,
+            [("c", vec![Function, Variable])]
+        );
+        // mixed.o has a C unit, a C++ unit and one without a language
+        assert_eq!(
+            tag_lines(&["--kinds", "c:v,c++:N,*:f"], "mixed.o"),
+            [
+                "d\timported.cpp\t:9;\"\tN",
+                "grid\tarrays.c\t:1;\"\tv\ttyperef:typename:int[3][4]",
+                "helper\timported.cpp\t:8;\"\tN",
+                "labels\tarrays.c\t:3;\"\tv\ttyperef:typename:const char *[2]",
+                "name\tarrays.c\t:2;\"\tv\ttyperef:typename:char[16]",
+                "untyped\tnolang.c\t:1;\"\tf",
+            ]
+        );
+        // the unit without a language gets the * kinds, or the default ones
+        assert_eq!(
+            tag_lines(&["--kinds", "C:f,*:v"], "mixed.o"),
+            ["size\tarrays.c\t:5;\"\tf"]
+        );
+        assert_eq!(
+            tag_lines(
+                &["--kind-map", "variable=V", "--kinds", "c:V,Cxx:N"],
+                "mixed.o"
+            ),
+            [
+                "d\timported.cpp\t:9;\"\tN",
+                "grid\tarrays.c\t:1;\"\tV\ttyperef:typename:int[3][4]",
+                "helper\timported.cpp\t:8;\"\tN",
+                "labels\tarrays.c\t:3;\"\tV\ttyperef:typename:const char *[2]",
+                "name\tarrays.c\t:2;\"\tV\ttyperef:typename:char[16]",
+                "untyped\tnolang.c\t:1;\"\tf",
+            ]
+        );
+    }
+
+    #[test]
+    fn using_declarations_and_namespace_aliases() {
+        let lines = tag_lines(&["--kinds", "fN"], "imported.o");
+        assert!(lines.contains(&"helper\timported.cpp\t:8;\"\tN".to_string()));
+        assert!(lines.contains(&"d\timported.cpp\t:9;\"\tN".to_string()));
+        // left out by default
+        let lines = tag_lines(&[], "imported.o");
+        assert!(lines.iter().all(|l| !l.ends_with("\tN")));
+    }
+
+    #[test]
+    fn relocatable_object() {
+        // the names and lines are only found once .rela.debug_info is applied
+        let options = fixture_options(&[], "reloc.o");
+        let mut stats = Stats::default();
+        let list = read_fixture(&options, &mut stats);
+        assert!(stats.relocatable);
+        assert_eq!(
+            format_tags(&list, &options),
+            ["first\treloc.c\t:3;\"\tf", "second\treloc.c\t:5;\"\tf"]
+        );
+    }
+
+    #[test]
+    fn line_table_with_md5_checksums() {
+        assert_eq!(
+            tag_lines(&[], "md5.o"),
+            ["helper\t./md5.h\t:2;\"\tf", "main\tmd5.c\t:4;\"\tf"]
+        );
+    }
+
+    #[test]
+    fn nameless_subprogram_named_from_the_symbol_table() {
+        assert_eq!(
+            tag_lines(&[], "ranges"),
+            ["split_up\tranges.c\t:0;\"\tf\tnamesrc:symtab"]
+        );
+    }
+
+    #[test]
+    fn outline_by_file() {
+        let options = fixture_options(&["--format", "outline"], "imported.o");
+        let list = read_fixture(&options, &mut Stats::default());
+        let mut out = Vec::new();
+        write_outline(&mut out, &list, &options).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "imported.cpp\n\
+             \t2\tf\thelper\tnamespace:lib\n\
+             \t4\tf\tdepth\tnamespace:lib::detail\n\
+             \t10\tf\trun\tnamespace:app\n"
+        );
+    }
+
+    #[test]
+    fn deep_nesting_and_circular_references() {
+        // the abstract origins of the two cycle DIEs lead to each other, each
+        // stops at --max-depth with a warning
+        let tags = ["cycle\tdeep.c\t:3;\"\tf", "nested\tdeep.c\t:9;\"\tf"];
+        let (lines, warnings) = tag_lines_and_warnings(&[], "deep.o");
+        assert_eq!(lines, tags);
+        assert_eq!(warnings, 2);
+        let (lines, warnings) = tag_lines_and_warnings(&["--max-depth", "1"], "deep.o");
+        assert_eq!(lines, tags);
+        assert_eq!(warnings, 2);
+    }
+
+    #[test]
+    fn visibility_field() {
+        assert_eq!(
+            tag_lines(&[], "visibility.o"),
+            [
+                "between\tvisibility.c\t:3;\"\tf\tvisibility:qualified",
+                "hidden\tvisibility.c\t:1;\"\tf\tvisibility:local",
+                "plain\tvisibility.c\t:4;\"\tf",
+                "shown\tvisibility.c\t:2;\"\tf\tvisibility:exported",
+            ]
+        );
+        assert!(tag_lines(&["--fields", "-visibility"], "visibility.o")
+            .iter()
+            .all(|l| !l.contains("visibility:")));
+    }
+
+    #[test]
+    fn no_header() {
+        let provenance = Provenance {
+            input_file: "a.out".to_string(),
+            build_id: Some("0123".to_string()),
+            comp_dir: Some("/src".to_string()),
+        };
+        let options = fixture_options(&["--emit-provenance"], "reloc.o");
+        assert_eq!(pseudo_tags(&options, &provenance).len(), 5);
+        let options = fixture_options(&["--emit-provenance", "--no-header"], "reloc.o");
+        assert!(pseudo_tags(&options, &provenance).is_empty());
+        let mut out = Vec::new();
+        let lines = tag_lines(&["--no-header"], "reloc.o");
+        write_tags(&mut out, &pseudo_tags(&options, &provenance), &lines).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "first\treloc.c\t:3;\"\tf\nsecond\treloc.c\t:5;\"\tf\n"
+        );
+    }
+
+    #[test]
+    fn split_units_from_several_dwo_files() {
+        let dwo_dir = fixture("");
+        let options = fixture_options(&["--dwo-dir", &dwo_dir], "dwo");
+        let mut stats = Stats::default();
+        let list = read_fixture(&options, &mut stats);
+        assert_eq!(stats.units, 2);
+        assert_eq!(stats.missing_dwos, 0);
+        assert_eq!(
+            format_tags(&list, &options),
+            [
+                "helper\t./dwo_helper.c\t:1;\"\tf",
+                "helper\t./dwo_main.c\t:1;\"\tf",
+                "main\t./dwo_main.c\t:3;\"\tf",
+            ]
+        );
+    }
+
+    #[test]
+    fn warnings_are_counted_per_run() {
+        assert_eq!(tag_lines_and_warnings(&[], "deep.o").1, 2);
+        // a clean run after it has none of its own
+        assert_eq!(tag_lines_and_warnings(&[], "reloc.o").1, 0);
+        assert_eq!(tag_lines_and_warnings(&[], "no-such-file").1, 1);
+    }
+
+    #[test]
+    fn array_dimensions_in_typeref() {
+        assert_eq!(
+            tag_lines(&["--kinds", "v"], "arrays.o"),
+            [
+                "grid\tarrays.c\t:1;\"\tv\ttyperef:typename:int[3][4]",
+                "labels\tarrays.c\t:3;\"\tv\ttyperef:typename:const char *[2]",
+                "name\tarrays.c\t:2;\"\tv\ttyperef:typename:char[16]",
+            ]
+        );
+    }
+
+    #[test]
+    fn merge_dir() {
+        let dir = temp_dir("merge");
+        let merge = |files: &[(&str, &[u8])]| {
+            for entry in fs::read_dir(&dir).unwrap() {
+                fs::remove_file(entry.unwrap().path()).unwrap();
+            }
+            for (name, data) in files {
+                fs::write(dir.join(name), data).unwrap();
+            }
+            let options = parse_args(["merge".to_string(), dir.display().to_string()].into_iter());
+            let mut stats = Stats::default();
+            let mut out = Vec::new();
+            write_merged_dir(&mut out, &options, &mut Profile::new(false), &mut stats);
+            (String::from_utf8(out).unwrap(), stats.warnings)
+        };
+        // the tags files of separate runs merge into what one run over all
+        // the objects writes
+        let tags_file = |input: &str| {
+            let options = fixture_options(&[], input);
+            let list = read_fixture(&options, &mut Stats::default());
+            let mut out = Vec::new();
+            let header = pseudo_tags(&options, &no_provenance());
+            write_tags(&mut out, &header, &format_tags(&list, &options)).unwrap();
+            out
+        };
+        let (merged, warnings) = merge(&[
+            ("arrays.tags", &tags_file("arrays.o")),
+            ("reloc.tags", &tags_file("reloc.o")),
+        ]);
+        assert_eq!(merged.as_bytes(), tags_file("objects.tar"));
+        assert!(warnings.is_empty());
+        let header = TAG_HEADER.join("\n");
+        // a BOM is skipped, pseudo tags all files agree on are kept
+        let (merged, warnings) = merge(&[
+            ("a.tags", b"\xef\xbb\xbf!_TAG_X\t1\t//\nb\ta.c\t:1;\"\tf\n"),
+            ("b.tags", b"!_TAG_X\t1\t//\na\tb.c\t:1;\"\tf\n"),
+        ]);
+        assert_eq!(
+            merged,
+            format!(
+                "{}\n!_TAG_X\t1\t//\na\tb.c\t:1;\"\tf\nb\ta.c\t:1;\"\tf\n",
+                header
+            )
+        );
+        assert!(warnings.is_empty());
+        // a pseudo tag they disagree on is dropped
+        let (merged, warnings) = merge(&[
+            ("a.tags", b"!_TAG_INPUT_FILE\ta.o\t//\nb\ta.c\t:1;\"\tf\n"),
+            ("b.tags", b"!_TAG_INPUT_FILE\tb.o\t//\na\tb.c\t:1;\"\tf\n"),
+        ]);
+        assert_eq!(
+            merged,
+            format!("{}\na\tb.c\t:1;\"\tf\nb\ta.c\t:1;\"\tf\n", header)
+        );
+        let (a, b) = (dir.join("a.tags"), dir.join("b.tags"));
+        assert_eq!(
+            warnings,
+            [format!(
+                "{} and {} give different values for !_TAG_INPUT_FILE, it is left out",
+                a.display(),
+                b.display()
+            )]
+        );
+        // other encodings are left out, another format is merged
+        let (merged, warnings) = merge(&[
+            ("a.tags", b"b\ta.c\t:1;\"\tf\n"),
+            ("b.tags", b"caf\xe9\tb.c\t:1;\"\tf\n"),
+            (
+                "c.tags",
+                b"!_TAG_FILE_ENCODING\tShift_JIS\t//\nc\tc.c\t:1;\"\tf\n",
+            ),
+            (
+                "d.tags",
+                b"!_TAG_FILE_ENCODING\tutf-8\t//\nd\td.c\t:1;\"\tf\n",
+            ),
+            ("e.tags", b"!_TAG_FILE_FORMAT\t1\t//\ne\te.c\t1\n"),
+        ]);
+        assert_eq!(
+            merged,
+            format!(
+                "{}\nb\ta.c\t:1;\"\tf\nd\td.c\t:1;\"\tf\ne\te.c\t1\n",
+                header
+            )
+        );
+        let name = |file: &str| dir.join(file).display().to_string();
+        assert_eq!(
+            warnings,
+            [
+                format!("{} is not UTF-8, it is left out", name("b.tags")),
+                format!(
+                    "{} is encoded in Shift_JIS, only UTF-8 tags files are merged, it is left out",
+                    name("c.tags")
+                ),
+                format!(
+                    "{} is a format 1 tags file, its lines are merged as they are",
+                    name("e.tags")
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn compare() {
+        let scoped = |name, file, line, scope: &str| FunctionInfo {
+            scope: Some((Kind::Namespace, scope.to_string())),
+            ..function(name, file, line)
+        };
+        let variable = |name, file, line| FunctionInfo {
+            kind: Kind::Variable,
+            ..function(name, file, line)
+        };
+        let old = [
+            function("kept", "a.c", 1),
+            function("moved", "a.c", 2),
+            function("gone", "a.c", 3),
+            function("counter", "a.c", 4),
+            scoped("put", "a.cpp", 5, "a"),
+        ];
+        let new = [
+            function("kept", "a.c", 1),
+            function("moved", "b.c", 2),
+            variable("counter", "a.c", 4),
+            scoped("put", "a.cpp", 5, "b"),
+            function("operator\"\" _q\\\u{1}", "a.cpp", 6),
+        ];
+        // kind and scope are part of what is matched
+        let diff = compare_tags(&old, &new);
+        let mut text = Vec::new();
+        write_diff_text(&mut text, &diff).unwrap();
+        assert_eq!(
+            String::from_utf8(text).unwrap(),
+            "+ counter (variable)\ta.c:4\n\
+             + operator\"\" _q\\\u{1} (function)\ta.cpp:6\n\
+             + put (function, namespace:b)\ta.cpp:5\n\
+             - counter (function)\ta.c:4\n\
+             - gone (function)\ta.c:3\n\
+             - put (function, namespace:a)\ta.cpp:5\n\
+             ~ moved (function)\ta.c:2 -> b.c:2\n\
+             3 added, 3 removed, 1 moved\n"
+        );
+        let mut json = Vec::new();
+        write_diff_json(&mut json, &diff).unwrap();
+        assert_eq!(
+            String::from_utf8(json).unwrap(),
+            r#"{
+  "added": [
+    {"name": "counter", "kind": "variable", "locations": [{"file": "a.c", "line": 4}]},
+    {"name": "operator\"\" _q\\\u0001", "kind": "function", "locations": [{"file": "a.cpp", "line": 6}]},
+    {"name": "put", "kind": "function", "scope": "namespace:b", "locations": [{"file": "a.cpp", "line": 5}]}
+  ],
+  "removed": [
+    {"name": "counter", "kind": "function", "locations": [{"file": "a.c", "line": 4}]},
+    {"name": "gone", "kind": "function", "locations": [{"file": "a.c", "line": 3}]},
+    {"name": "put", "kind": "function", "scope": "namespace:a", "locations": [{"file": "a.cpp", "line": 5}]}
+  ],
+  "moved": [
+    {"name": "moved", "kind": "function", "old": [{"file": "a.c", "line": 2}], "new": [{"file": "b.c", "line": 2}]}
+  ]
+}
+"#
+        );
+        // nothing changed
+        let mut json = Vec::new();
+        write_diff_json(&mut json, &compare_tags(&old, &old)).unwrap();
+        assert_eq!(
+            String::from_utf8(json).unwrap(),
+            "{\n  \"added\": [],\n  \"removed\": [],\n  \"moved\": []\n}\n"
+        );
+        assert_eq!(json_string("a\tb\nc"), "\"a\\tb\\nc\"");
+    }
+
+    #[test]
+    fn load_bias() {
+        // the lowest PT_LOAD of fixed-base is at 0x400000, first at 0x401000
+        let addresses = |args: &[&str]| {
+            tag_lines(&[&["--addresses"], args].concat(), "fixed-base")
+                .iter()
+                .map(|line| line.rsplit('\t').next().unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(addresses(&[]), ["address:0x401000", "address:0x40100c"]);
+        assert_eq!(
+            addresses(&["--load-bias", "0"]),
+            ["address:0x1000", "address:0x100c"]
+        );
+        assert_eq!(
+            addresses(&["--load-bias", "0x7f0000000000"]),
+            ["address:0x7f0000001000", "address:0x7f000000100c"]
+        );
+        assert_eq!(
+            addresses(&["--load-bias", "4096"]),
+            ["address:0x2000", "address:0x200c"]
+        );
+        // a shared object is linked at 0, the bias is just added
+        assert_eq!(
+            tag_lines(&["--addresses", "--load-bias", "0x10000"], "provenance")
+                .iter()
+                .map(|line| line.rsplit('\t').next().unwrap())
+                .collect::<Vec<_>>(),
+            ["address:0x11000", "address:0x1100c"]
+        );
+        assert_eq!(check_load_bias(None, &["kind"]), Ok(()));
+        assert_eq!(check_load_bias(Some(0), &["kind", "address"]), Ok(()));
+        assert_eq!(
+            check_load_bias(Some(0), &["kind"]),
+            Err("--load-bias only works with --addresses")
+        );
+    }
+
+    #[test]
+    fn default_lower_bounds() {
+        assert_eq!(default_lower_bound(Some(gimli::DW_LANG_C99)), 0);
+        assert_eq!(default_lower_bound(Some(gimli::DW_LANG_C_plus_plus_17)), 0);
+        assert_eq!(default_lower_bound(Some(gimli::DW_LANG_Go)), 0);
+        assert_eq!(default_lower_bound(Some(gimli::DW_LANG_Java)), 0);
+        assert_eq!(default_lower_bound(Some(gimli::DW_LANG_Ada2012)), 1);
+        assert_eq!(default_lower_bound(Some(gimli::DW_LANG_Fortran90)), 1);
+        assert_eq!(default_lower_bound(Some(gimli::DW_LANG_Pascal83)), 1);
+        assert_eq!(default_lower_bound(None), 0);
+    }
+
+    #[test]
+    fn dedup_window_forgets_the_oldest_tag() {
+        let (a, b, c) = (
+            function("a", "a.c", 1),
+            function("b", "a.c", 2),
+            function("c", "a.c", 3),
+        );
+        let mut window = DedupWindow::new(2);
+        assert!(window.insert(&a));
+        assert!(window.insert(&b));
+        assert!(!window.insert(&a));
+        // pushes a out of the window, so it's written again
+        assert!(window.insert(&c));
+        assert!(window.insert(&a));
+        assert!(!window.insert(&c));
+        // the same name elsewhere isn't a repeat
+        assert!(window.insert(&function("c", "b.c", 3)));
+        let mut window = DedupWindow::new(0);
+        assert!(window.insert(&a));
+        assert!(window.insert(&a));
+    }
+
+    #[test]
+    fn same_tags_on_every_run_and_byte_order() {
+        let little = tag_lines(&["--addresses"], "endian-le.o");
+        assert_eq!(
+            little,
+            [
+                "first\tendian.c\t:1;\"\tf\taddress:0x0",
+                "second\tendian.c\t:6;\"\tf\taddress:0x10",
+            ]
+        );
+        // the same but for the address of second, the code is longer
+        let big = tag_lines(&["--addresses"], "endian-be.o");
+        for _ in 0..3 {
+            assert_eq!(tag_lines(&["--addresses"], "endian-be.o"), big);
+        }
+        assert_eq!(big[0], little[0]);
+        assert_eq!(big[1], "second\tendian.c\t:6;\"\tf\taddress:0x14");
+        // of the symbols for the nameless subprogram's address the first by
+        // name is used, not the first or last in the symbol table
+        assert_eq!(
+            tag_lines(&[], "aliases"),
+            ["a_work\taliases.c\t:0;\"\tf\tnamesrc:symtab"]
+        );
+    }
+
+    #[test]
+    fn only_the_string_sections_compressed() {
+        let expected = tag_lines(&[], "md5.o");
+        assert_eq!(expected.len(), 2);
+        assert_eq!(
+            tag_lines_and_warnings(&[], "md5-str-zlib.o"),
+            (expected.clone(), 0)
+        );
+        assert_eq!(
+            tag_lines_and_warnings(&[], "md5-str-offsets-zstd.o"),
+            (expected, 0)
+        );
+    }
+
+    #[test]
+    fn go_names() {
+        let scoped =
+            |name: &str, kind, scope: &str| (name.to_string(), Some((kind, scope.to_string())));
+        assert_eq!(
+            split_go_name("net/http.Get", "."),
+            scoped("Get", Kind::Package, "net/http")
+        );
+        assert_eq!(
+            split_go_name("net/http.(*Client).Do", "."),
+            scoped("Do", Kind::Type, "net/http.Client")
+        );
+        assert_eq!(
+            split_go_name("main.T.String", "::"),
+            scoped("String", Kind::Type, "main::T")
+        );
+        assert_eq!(
+            split_go_name("main.run.func1", "."),
+            scoped("func1", Kind::Function, "main.run")
+        );
+        assert_eq!(split_go_name("runtime", "."), ("runtime".to_string(), None));
+    }
+
+    #[test]
+    fn scope_separator() {
+        let lines = tag_lines(&["--scope-separator", "."], "imported.o");
+        assert!(lines.contains(&"depth\timported.cpp\t:4;\"\tf\tnamespace:lib.detail".to_string()));
+        let options = fixture_options(&[], "imported.o");
+        let separator = |language| super::scope_separator(Some(language), &options);
+        assert_eq!(separator(gimli::DW_LANG_C_plus_plus_14), "::");
+        assert_eq!(separator(gimli::DW_LANG_Rust), "::");
+        assert_eq!(separator(gimli::DW_LANG_Ada95), ".");
+        assert_eq!(separator(gimli::DW_LANG_Fortran08), ".");
+        assert_eq!(separator(gimli::DW_LANG_Go), ".");
+        assert_eq!(separator(gimli::DW_LANG_Java), ".");
+        assert_eq!(super::scope_separator(None, &options), "::");
+        let options = fixture_options(&["--scope-separator", "/"], "imported.o");
+        assert_eq!(
+            super::scope_separator(Some(gimli::DW_LANG_Ada95), &options),
+            "/"
+        );
+    }
+
+    #[test]
+    fn second_run_reads_the_cache() {
+        let dir = temp_dir("cache");
+        let cache_dir = dir.to_str().unwrap();
+        let run = |args: &[&str]| {
+            let options = fixture_options(args, "imported.o");
+            let mut stats = Stats::default();
+            let list = read_fixture(&options, &mut stats);
+            (format_tags(&list, &options), stats.cache_hits)
+        };
+        let (first, hits) = run(&["--cache-dir", cache_dir]);
+        assert_eq!(hits, 0);
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        assert_eq!(run(&["--cache-dir", cache_dir]), (first.clone(), 1));
+        // an option that changes the tags needs an entry of its own
+        let (scoped, hits) = run(&["--cache-dir", cache_dir, "--scope-separator", "."]);
+        assert_eq!(hits, 0);
+        assert_ne!(scoped, first);
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 2);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn specification_of_a_type_in_debug_types() {
+        // the struct is in a type unit, the compile unit only has a stub of
+        // it pointing there, with the declarations of the methods
+        assert_eq!(
+            tag_lines(&[], "types.o"),
+            [
+                "area\ttypes.cpp\t:4;\"\tf\tstruct:shapes::Circle",
+                "area\ttypes.cpp\t:9;\"\tf\tstruct:shapes::Circle",
+                "unit\ttypes.cpp\t:5;\"\tf\tstruct:shapes::Circle",
+                "unit\ttypes.cpp\t:14;\"\tf\tstruct:shapes::Circle",
+            ]
+        );
+    }
+
+    #[test]
+    fn normalize_paths() {
+        assert_eq!(normalize_path("./src//a/../b.c"), "src/b.c");
+        assert_eq!(normalize_path("../../a.c"), "../../a.c");
+        assert_eq!(normalize_path("a/../../b.c"), "../b.c");
+        assert_eq!(normalize_path("/../usr/./include/"), "/usr/include");
+        assert_eq!(normalize_path("a/.."), ".");
+        assert_eq!(
+            tag_lines(&["--normalize-paths"], "md5.o"),
+            ["helper\tmd5.h\t:2;\"\tf", "main\tmd5.c\t:4;\"\tf"]
+        );
+    }
+
+    #[test]
+    fn directory_groups() {
+        assert_eq!(directory_group("src/net/tcp.c", 1).as_deref(), Some("src"));
+        assert_eq!(
+            directory_group("src/net/tcp.c", 2).as_deref(),
+            Some("src_net")
+        );
+        assert_eq!(
+            directory_group("/usr/include/stdio.h", 1).as_deref(),
+            Some("usr")
+        );
+        assert_eq!(directory_group("main.c", 1), None);
+        assert_eq!(directory_group("src/tcp.c", 2), None);
+        // cleaned up first
+        assert_eq!(directory_group("./src//net/../tcp.c", 2), None);
+        assert_eq!(
+            directory_group("src\\net\\tcp.c", 2).as_deref(),
+            Some("src_net")
+        );
+        assert_eq!(directory_group("src/../../lib/a.c", 1), None);
+        assert_eq!(directory_group("../lib/a.c", 1), None);
+    }
+
+    #[test]
+    fn tags_before_the_damage_are_kept() {
+        let (tags, warnings) = tag_lines_and_warnings(&[], "damaged.o");
+        assert_eq!(
+            tags,
+            [
+                "before_damage\tdamaged.c\t:4;\"\tf",
+                "caf\u{fffd}\tdamaged.c\t:2;\"\tf",
+                "intact\tdamaged.c\t:3;\"\tf",
+            ]
+        );
+        // the damaged DIE, the rest of its unit and the cut off unit
+        assert_eq!(warnings, 3);
+    }
+
+    #[test]
+    fn line_zero_policies() {
+        assert_eq!(escape_pattern("a/b\\c", None), "a\\/b\\\\c");
+        assert_eq!(escape_pattern("\tx", Some(TabReplace::Spaces(2))), "  x");
+        assert_eq!(escape_pattern("\tx", Some(TabReplace::Escape)), "\\tx");
+        let line_zero = |policy| tag_lines(&["--line-zero", policy], "ranges")[0].clone();
+        assert_eq!(
+            line_zero("keep"),
+            "split_up\tranges.c\t:0;\"\tf\tnamesrc:symtab"
+        );
+        assert_eq!(
+            line_zero("as-one"),
+            "split_up\tranges.c\t:1;\"\tf\tnamesrc:symtab"
+        );
+        assert_eq!(
+            line_zero("omit"),
+            "split_up\tranges.c\t/split_up/;\"\tf\tnamesrc:symtab"
+        );
+    }
+
+    #[test]
+    fn macros_from_debug_macro_and_debug_macinfo() {
+        let expected = [
+            "BUF_SIZE\tmacros.c\t:1;\"\td",
+            "MAX\tmacros.c\t:2;\"\td",
+            "biggest\tmacros.c\t:4;\"\tf",
+        ];
+        assert_eq!(tag_lines(&[], "macros.o"), expected);
+        assert_eq!(tag_lines(&[], "macinfo.o"), expected);
+        assert_eq!(tag_lines(&["--kinds", "f"], "macros.o"), expected[2..]);
+    }
+
+    #[test]
+    fn strip_template_args_from_scope() {
+        assert_eq!(
+            strip_template_args("ns::Foo<int, Bar<char> >::Inner<2>"),
+            "ns::Foo::Inner"
+        );
+        assert_eq!(strip_template_args("Foo<std::pair<a::b, c>>"), "Foo");
+        // unbalanced, so left alone
+        assert_eq!(strip_template_args("Foo<int"), "Foo<int");
+        assert_eq!(strip_template_args("Foo>::Bar"), "Foo>::Bar");
+        assert_eq!(
+            tag_lines(&[], "templates.o")[0],
+            "first\ttemplates.cpp\t:5;\"\tf\tstruct:box::Array<char, 2>"
+        );
+        assert_eq!(
+            tag_lines(&["--strip-template-args-from-scope"], "templates.o")[0],
+            "first\ttemplates.cpp\t:5;\"\tf\tstruct:box::Array"
+        );
+    }
+
+    #[test]
+    fn prototyped_field() {
+        assert_eq!(
+            tag_lines(&["--fields", "+prototyped"], "prototyped.o"),
+            [
+                "new_style\tprototyped.c\t:8;\"\tf\tprototyped:true",
+                "old_style\tprototyped.c\t:1;\"\tf\tprototyped:false",
+            ]
+        );
+        // only for C units
+        assert!(tag_lines(&["--fields", "+prototyped"], "imported.o")
+            .iter()
+            .all(|l| !l.contains("prototyped:")));
+    }
+
+    #[test]
+    fn doc_comments() {
+        let lines = |text: &str| text.lines().map(str::to_string).collect::<Vec<_>>();
+        let c =
+            lines("int x;\n/** Adds\n * two numbers. */\nint add(int, int);\n\n// gap\n\nint f();");
+        assert_eq!(
+            doc_comment("a.c", &c, 3),
+            Some("Adds two numbers.".to_string())
+        );
+        // a blank line between them
+        assert_eq!(doc_comment("a.c", &c, 7), None);
+        assert_eq!(doc_comment("a.c", &c, 0), None);
+        let cpp = lines("/// first\n/// second \\ third\nvoid g();");
+        assert_eq!(
+            doc_comment("a.cpp", &cpp, 2),
+            Some("first second \\\\ third".to_string())
+        );
+        let ada = lines("-- Does it\nprocedure Do_It;");
+        assert_eq!(doc_comment("p.ads", &ada, 1), Some("Does it".to_string()));
+        assert_eq!(doc_comment("p.c", &ada, 1), None);
+        let long = lines(&format!("// {}\nvoid h();", "word ".repeat(100)));
+        let doc = doc_comment("a.c", &long, 1).unwrap();
+        assert_eq!(doc.chars().count(), DOC_MAX_LEN + 3);
+        assert!(doc.ends_with("..."));
+    }
+
+    #[test]
+    fn objects_in_a_tar_archive() {
+        assert_eq!(
+            tag_lines(&[], "objects.tar"),
+            [
+                "first\treloc.c\t:3;\"\tf",
+                "second\treloc.c\t:5;\"\tf",
+                "size\tarrays.c\t:5;\"\tf",
+            ]
+        );
+    }
+
+    #[test]
+    fn tags_from_memory() {
+        let data = fs::read(fixture("reloc.o")).unwrap();
+        let name = fixture("reloc.o");
+        // the same as reading the file, with the format given or not
+        assert_eq!(
+            extract_tags_with_format(&name, &data, InputFormat::Elf),
+            tag_lines(&[], "reloc.o")
+        );
+        let archive = fs::read(fixture("objects.tar")).unwrap();
+        assert_eq!(
+            extract_tags_with_format("objects.tar", &archive, InputFormat::Tar),
+            tag_lines(&[], "objects.tar")
+        );
+        // a tar archive isn't looked for in what is said to be an elf file
+        assert!(extract_tags_with_format("objects.tar", &archive, InputFormat::Elf).is_empty());
+        assert!(extract_tags_with_format(&name, &data, InputFormat::Tar).is_empty());
+    }
+
+    #[test]
+    fn rust_names_are_demangled_once() {
+        let name = "_ZN6traits6Meters6double17hb5a3bcb0448eab8eE";
+        let mut demangled = Demangled::new();
+        assert_eq!(
+            demangle_rust(name, &mut demangled).as_deref(),
+            Some("traits::Meters::double")
+        );
+        assert_eq!(
+            demangle_rust(name, &mut demangled).as_deref(),
+            Some("traits::Meters::double")
+        );
+        assert_eq!(demangled.len(), 1);
+        // a name already in the map isn't demangled again, whatever it holds
+        demangled.insert(name.to_string(), Some("cached".to_string()));
+        assert_eq!(
+            demangle_rust(name, &mut demangled).as_deref(),
+            Some("cached")
+        );
+        assert_eq!(rust_shim(name, &mut demangled), None);
+        demangled.insert(
+            "not_mangled".to_string(),
+            Some("x::{shim:vtable#0}".to_string()),
+        );
+        assert_eq!(
+            rust_shim("not_mangled", &mut demangled).as_deref(),
+            Some("{shim:vtable#0}")
+        );
+        assert_eq!(demangled.len(), 2);
+    }
+
+    #[test]
+    fn rust_trait_impls() {
+        let mut demangled = Demangled::new();
+        assert_eq!(
+            rust_trait_impl(
+                "_ZN65_$LT$traits..Meters$u20$as$u20$core..convert..From$LT$f64$GT$$GT$4from17h39df240462d655bcE",
+                &mut demangled
+            ),
+            Some("core::convert::From<f64> for traits::Meters".to_string())
+        );
+        assert_eq!(
+            rust_trait_impl(
+                "_ZN6traits6Meters6double17hb5a3bcb0448eab8eE",
+                &mut demangled
+            ),
+            None
+        );
+        assert_eq!(rust_trait_impl("not_mangled", &mut demangled), None);
+        assert_eq!(demangled.len(), 3);
+        let lines = tag_lines(&["--trait-impls"], "traits.o");
+        let lines: Vec<&String> = lines
+            .iter()
+            .filter(|l| l.contains("\t./traits.rs\t"))
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                "double\t./traits.rs\t:12;\"\tf\tstruct:traits::Meters",
+                "fmt\t./traits.rs\t:6;\"\tf\tnamespace:traits::{impl#0}\timpl:core::fmt::Display for traits::Meters",
+                "from\t./traits.rs\t:18;\"\tf\tnamespace:traits::{impl#2}\timpl:core::convert::From<f64> for traits::Meters",
+            ]
+        );
+    }
+
+    #[test]
+    fn signatures_with_qualifiers() {
+        let lines = tag_lines(&["--fields", "+signature"], "qualifiers.o");
+        let signatures: Vec<(&str, &str)> = lines
+            .iter()
+            .map(|l| {
+                let (name, rest) = l.split_once('\t').unwrap();
+                (name, rest.rsplit_once("\tsignature:").unwrap().1)
+            })
+            .collect();
+        // by name and line, the declarations in X before the definitions
+        assert_eq!(
+            signatures,
+            [
+                ("f", "() const"),
+                ("f", "()"),
+                ("f", "() const"),
+                ("f", "()"),
+                ("g", "() &&"),
+                ("g", "() &"),
+                ("g", "() &&"),
+                ("g", "() &"),
+                ("h", "(int, const char *, ...)"),
+                ("h", "(int, const char *, ...)"),
+                ("k", "(int) const volatile"),
+                ("use", "(X &)"),
+            ]
+        );
+    }
+
+    #[test]
+    fn max_units() {
+        let (tags, warnings) = tag_lines_and_warnings(&["--max-units", "1"], "damaged.o");
+        assert_eq!(
+            tags,
+            [
+                "caf\u{fffd}\tdamaged.c\t:2;\"\tf",
+                "intact\tdamaged.c\t:3;\"\tf",
+            ]
+        );
+        // only the one saying the rest is left out, the damage is after the
+        // first unit
+        assert_eq!(warnings, 1);
+    }
+
+    #[test]
+    fn max_units_run_isnt_cached() {
+        let dir = temp_dir("max-units-cache");
+        let options = fixture_options(
+            &["--max-units", "1", "--cache-dir", dir.to_str().unwrap()],
+            "anonymous.o",
+        );
+        let mut stats = Stats::default();
+        let list = read_fixture(&options, &mut stats);
+        assert_eq!(list.len(), 2);
+        assert_eq!(stats.warnings.len(), 1);
+        // so the cache key doesn't need --max-units
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn implicit_const_decl_attributes() {
+        assert_eq!(
+            tag_lines(&[], "implicit.o"),
+            [
+                "one\timplicit.c\t:10;\"\tf",
+                "three\timplicit.c\t:300;\"\tf",
+                "two\timplicit.c\t:20;\"\tf",
+            ]
+        );
+    }
+
+    #[test]
+    fn output_bom() {
+        let dir = temp_dir("output-bom");
+        let tags = dir.join("tags");
+        let tags_path = tags.to_str().unwrap();
+        let merge = |args: &[&str], input: &str| {
+            let mut args = args.to_vec();
+            args.extend(["--merge-into", tags_path]);
+            let options = fixture_options(&args, input);
+            let list = read_fixture(&options, &mut Stats::default());
+            let header = pseudo_tags(&options, &no_provenance());
+            merge_into(
+                tags_path,
+                &list,
+                &header,
+                &options,
+                &mut SourceCache::default(),
+            )
+            .unwrap();
+            fs::read(&tags).unwrap()
+        };
+        let with_bom = merge(&["--output-bom", "--no-header"], "reloc.o");
+        assert_eq!(
+            with_bom,
+            b"\xef\xbb\xbffirst\treloc.c\t:3;\"\tf\nsecond\treloc.c\t:5;\"\tf\n"
+        );
+        // merging into it without --output-bom drops the BOM but keeps the
+        // tags of the other file
+        let without = merge(&["--no-header"], "arrays.o");
+        assert_eq!(
+            String::from_utf8(without).unwrap(),
+            "first\treloc.c\t:3;\"\tf\nsecond\treloc.c\t:5;\"\tf\nsize\tarrays.c\t:5;\"\tf\n"
+        );
+        let groups = dir.join("groups");
+        let options = fixture_options(
+            &[
+                "--output-bom",
+                "--group-by-directory",
+                groups.to_str().unwrap(),
+            ],
+            "reloc.o",
+        );
+        let list = read_fixture(&options, &mut Stats::default());
+        let header = pseudo_tags(&options, &no_provenance());
+        write_directory_groups(
+            groups.to_str().unwrap(),
+            &list,
+            &header,
+            &options,
+            &mut SourceCache::default(),
+        )
+        .unwrap();
+        let root = fs::read(groups.join("_root.tags")).unwrap();
+        assert!(root.starts_with(b"\xef\xbb\xbf!_TAG_FILE_FORMAT\t"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn anonymous_namespaces_scoped_by_file() {
+        assert_eq!(
+            tag_lines(&["--group-anonymous-by-file"], "anonymous.o"),
+            [
+                "helper\tanonymous_a.cpp\t:2;\"\tf\tnamespace:anonymous_a.cpp::<anonymous>",
+                "helper\tanonymous_b.cpp\t:2;\"\tf\tnamespace:anonymous_b.cpp::<anonymous>",
+                "use_a\tanonymous_a.cpp\t:5;\"\tf",
+                "use_b\tanonymous_b.cpp\t:5;\"\tf",
+            ]
+        );
+        assert_eq!(
+            tag_lines(&[], "anonymous.o")[..2],
+            [
+                "helper\tanonymous_a.cpp\t:2;\"\tf",
+                "helper\tanonymous_b.cpp\t:2;\"\tf",
+            ]
+        );
+    }
+
+    #[test]
+    fn supplementary_file() {
+        // the names are all DW_FORM_strp_sup, into the .debug_str of the
+        // sup.debug named by .debug_sup, found next to the file
+        let tags = ["main\tsup.c\t:6;\"\tf", "shared_helper\tsup.c\t:1;\"\tf"];
+        assert_eq!(tag_lines(&[], "sup-main"), tags);
+        let dir = temp_dir("sup-file");
+        let moved = dir.join("moved.debug");
+        fs::copy(fixture("sup.debug"), &moved).unwrap();
+        assert_eq!(
+            tag_lines(&["--sup-file", moved.to_str().unwrap()], "sup-main"),
+            tags
+        );
+        // without it the names come from the symbol table
+        fs::remove_file(&moved).unwrap();
+        let (lines, warnings) =
+            tag_lines_and_warnings(&["--sup-file", moved.to_str().unwrap()], "sup-main");
+        assert_eq!(
+            lines,
+            [
+                "main\tsup.c\t:6;\"\tf\tnamesrc:symtab",
+                "shared_helper\tsup.c\t:1;\"\tf\tnamesrc:symtab",
+            ]
+        );
+        assert_eq!(warnings, 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn dedup_report() {
+        let dir = temp_dir("dedup-report");
+        let report = dir.join("report");
+        let report_path = report.to_str().unwrap();
+        // both units describe the static inline function of the header
+        let twice = "twice\t./repeated.h\t:1;\"\tf";
+        assert_eq!(
+            tag_lines(&["--dedup-report", report_path], "repeated.o"),
+            [
+                twice,
+                "use_one\trepeated_a.c\t:3;\"\tf",
+                "use_two\trepeated_b.c\t:3;\"\tf",
+            ]
+        );
+        assert_eq!(fs::read_to_string(&report).unwrap(), format!("{}\n", twice));
+        // nothing removed, an empty report
+        tag_lines(&["--dedup-report", report_path], "reloc.o");
+        assert_eq!(fs::read_to_string(&report).unwrap(), "");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn rust_closures() {
+        // the tags of closures.rs, and of the shim through which boxed's
+        // closure is called as a dyn FnOnce
+        let closures = |mode: &str| -> Vec<String> {
+            tag_lines(&["--rust-closures", mode], "closures.o")
+                .into_iter()
+                .filter(|line| line.contains("\t./closures.rs\t") || line.starts_with("call_once"))
+                .map(|line| line.split('\t').next().unwrap().to_string())
+                .collect()
+        };
+        assert_eq!(closures("skip"), ["boxed", "nested", "total"]);
+        assert_eq!(
+            closures("keep"),
+            [
+                "boxed",
+                "call_once<closures::boxed::{closure_env#0}, ()>",
+                "nested",
+                "total",
+                "{closure#0}",
+                "{closure#0}",
+                "{closure#0}",
+                "{closure#0}",
+            ]
+        );
+        assert_eq!(
+            closures("qualify"),
+            [
+                "boxed",
+                "boxed::{closure#0}",
+                "call_once<closures::boxed::{closure_env#0}, ()>::{shim:vtable#0}",
+                "nested",
+                "nested::{closure#0}",
+                "nested::{closure#0}::{closure#0}",
+                "total",
+                "total::{closure#0}",
+            ]
+        );
+    }
+
+    #[test]
+    fn qualified_closure_names() {
+        assert_eq!(
+            qualify_rust_closure(
+                "{closure#0}",
+                Some("std::fmt::_print_fmt::{closure#1}"),
+                "::"
+            ),
+            "_print_fmt::{closure#1}::{closure#0}"
+        );
+        assert_eq!(
+            qualify_rust_closure("{closure#2}", Some("run"), "."),
+            "run.{closure#2}"
+        );
+        assert_eq!(
+            qualify_rust_closure("{closure#0}", None, "::"),
+            "{closure#0}"
+        );
+    }
+
+    #[test]
+    fn rust_shims() {
+        let mut demangled = Demangled::new();
+        assert_eq!(
+            rust_shim(
+                "_ZN4core3ops8function6FnOnce40call_once$u7b$$u7b$vtable.shim$u7d$$u7d$17h47ef11d0d33bfd5dE",
+                &mut demangled
+            )
+            .as_deref(),
+            Some("call_once{{vtable.shim}}")
+        );
+        assert_eq!(
+            rust_shim("_ZN8closures5boxed17h0123456789abcdefE", &mut demangled),
+            None
+        );
+        // not Rust
+        assert_eq!(rust_shim("_Z3fooi", &mut demangled), None);
+    }
+
+    #[test]
+    fn broken_section_headers() {
+        // a .debug_info running past the end of the file is left out, with
+        // one warning and none for its relocations
+        assert_eq!(tag_lines_and_warnings(&[], "bad-size.o"), (Vec::new(), 1));
+        // without .debug_str there are no names
+        assert_eq!(tag_lines_and_warnings(&[], "bad-offset.o"), (Vec::new(), 1));
+        // the relocations are left out, so the string offsets all point to
+        // the start of .debug_str, but the lines are still read
+        let (tags, warnings) = tag_lines_and_warnings(&[], "bad-relocations.o");
+        let lines: Vec<&str> = tags
+            .iter()
+            .map(|line| line.split('\t').nth(2).unwrap())
+            .collect();
+        assert_eq!(lines, [":3;\"", ":5;\""]);
+        assert_eq!(warnings, 1);
+        // only the one relocation outside .debug_info is left out
+        let (tags, warnings) = tag_lines_and_warnings(&[], "bad-relocation.o");
+        assert_eq!(
+            tags,
+            ["first\treloc.c\t:3;\"\tf", "second\treloc.c\t:5;\"\tf"]
+        );
+        assert_eq!(warnings, 1);
+    }
+
+    #[test]
+    fn only_with_code() {
+        assert_eq!(
+            tag_lines(&[], "code.o"),
+            [
+                "compiled\tcode.cpp\t:19;\"\tf",
+                "declared\tcode.cpp\t:1;\"\tf",
+                "inlined\tcode.cpp\t:3;\"\tf",
+                "next\tcode.cpp\t:10;\"\tf\tstruct:Counter",
+                "next\tcode.cpp\t:14;\"\tf\tstruct:Counter",
+                "unused\tcode.cpp\t:11;\"\tf\tstruct:Counter",
+            ]
+        );
+        // the extern and the always inlined function, and the declarations
+        // of the methods, are left out
+        assert_eq!(
+            tag_lines(&["--only-with-code"], "code.o"),
+            [
+                "compiled\tcode.cpp\t:19;\"\tf",
+                "next\tcode.cpp\t:14;\"\tf\tstruct:Counter",
+            ]
+        );
+    }
+
+    #[test]
+    fn collation_keys() {
+        let mut names = ["Zebra", "ecrire", "école", "Apple2", "apple", "_private"];
+        names.sort_by_cached_key(|name| collation_key(name));
+        assert_eq!(
+            names,
+            ["_private", "apple", "Apple2", "école", "ecrire", "Zebra"]
+        );
+        // accents before case, and only equal names are equal
+        assert!(collation_key("ecole") < collation_key("Ecole"));
+        assert!(collation_key("Ecole") < collation_key("école"));
+        assert!(collation_key("straße") < collation_key("strasse2"));
+        assert!(collation_key("ß") != collation_key("ss"));
+    }
+
+    #[test]
+    fn folded_collation() {
+        let names = |args: &[&str]| -> Vec<String> {
+            tag_lines(args, "unicode.o")
+                .iter()
+                .map(|line| line.split('\t').next().unwrap().to_string())
+                .collect()
+        };
+        assert_eq!(
+            names(&[]),
+            ["Apple2", "Zebra", "_private", "apple", "ecrire", "école"]
+        );
+        assert_eq!(
+            names(&["--collation", "fold"]),
+            ["_private", "apple", "Apple2", "école", "ecrire", "Zebra"]
+        );
+        // editors can't binary search it
+        let options = fixture_options(&["--collation", "fold"], "unicode.o");
+        assert!(pseudo_tags(&options, &no_provenance())
+            .iter()
+            .any(|line| line.starts_with("!_TAG_FILE_SORTED\t0\t")));
+    }
+
+    #[test]
+    fn missing_sources_of_a_run() {
+        let options = fixture_options(
+            &["--excmd", "pattern", "--fail-on-missing-source"],
+            "reloc.o",
+        );
+        let found = function("first", &fixture("reloc.c"), 3);
+        let gone = function("gone", "/nonexistent/gone.c", 1);
+        let mut sources = SourceCache::default();
+        for f in [&found, &gone, &gone] {
+            format_file_info(f, &mut sources, &options);
+        }
+        assert_eq!(
+            sources.missing.iter().collect::<Vec<_>>(),
+            ["/nonexistent/gone.c"]
+        );
+        // the next run starts with nothing missing
+        let mut sources = SourceCache::default();
+        format_file_info(&found, &mut sources, &options);
+        assert!(sources.missing.is_empty());
+    }
+
+    #[test]
+    fn on_empty() {
+        // the end of a run, after the tags were written
+        let finish = |args: &[&str], stats: &mut Stats| {
+            let options = fixture_options(args, "reloc.o");
+            check_empty(&options, stats)
+                .map_err(|msg| (msg, EXIT_FAILURE))
+                .and_then(|_| check_warnings(&options, stats))
+        };
+        let path = fixture("reloc.o");
+        let empty = format!(
+            "no tags found in {}, was it built with debug info (-g)?",
+            path
+        );
+        let mut stats = Stats::default();
+        assert_eq!(finish(&["--on-empty", "ignore"], &mut stats), Ok(()));
+        assert_eq!(stats.warnings.len(), 0);
+        // warn is the default
+        let mut stats = Stats::default();
+        assert_eq!(finish(&[], &mut stats), Ok(()));
+        assert_eq!(stats.warnings.len(), 1);
+        let mut stats = Stats::default();
+        assert_eq!(
+            finish(&["--on-empty", "error"], &mut stats),
+            Err((empty.clone(), 1))
+        );
+        // the empty warning counts like any other
+        let mut stats = Stats::default();
+        assert_eq!(
+            finish(&["--exit-code-on-warnings", "3"], &mut stats),
+            Err(("1 warning(s) were printed".to_string(), 3))
+        );
+        let mut stats = Stats::default();
+        assert_eq!(
+            finish(&["--on-empty", "ignore", "--werror"], &mut stats),
+            Ok(())
+        );
+        // error takes precedence, whatever N is
+        let mut stats = Stats::default();
+        assert_eq!(
+            finish(
+                &["--on-empty", "error", "--exit-code-on-warnings", "3"],
+                &mut stats
+            ),
+            Err((empty, 1))
+        );
+        // a run with tags is left alone, unless it warned
+        let mut stats = Stats {
+            tags: 2,
+            ..Stats::default()
+        };
+        assert_eq!(
+            finish(&["--on-empty", "error", "--werror"], &mut stats),
+            Ok(())
+        );
+        stats.warn("something");
+        assert_eq!(
+            finish(&["--on-empty", "error", "--werror"], &mut stats),
+            Err(("1 warning(s) were printed".to_string(), 1))
+        );
+    }
+
+    #[test]
+    fn unit_counts_by_dwarf_version() {
+        let read = |input: &str| {
+            let mut stats = Stats::default();
+            read_fixture(&fixture_options(&[], input), &mut stats);
+            stats
+        };
+        let dwarf4 = read("macinfo.o");
+        assert_eq!(dwarf4.units, 1);
+        assert_eq!(dwarf4.unit_versions, BTreeMap::from([(4, 1)]));
+        assert_eq!(dwarf4.subprograms, 1);
+        assert!(dwarf4.relocatable);
+        // both objects of the archive
+        let dwarf5 = read("objects.tar");
+        assert_eq!(dwarf5.units, 2);
+        assert_eq!(dwarf5.unit_versions, BTreeMap::from([(5, 2)]));
+        assert_eq!(dwarf5.subprograms, 3);
+        let mut total = Stats::default();
+        total.add(&dwarf4);
+        total.add(&dwarf5);
+        assert_eq!(total.units, 3);
+        assert_eq!(total.unit_versions, BTreeMap::from([(4, 1), (5, 2)]));
+    }
+
+    #[test]
+    fn profile_phases() {
+        let options = fixture_options(&["--profile"], "objects.tar");
+        let mut profile = Profile::new(options.profile);
+        let mut stream = None::<TagStream<std::io::Sink>>;
+        let mut stats = Stats::default();
+        extract_tags(
+            &options.path,
+            &options,
+            &mut profile,
+            &mut stats,
+            &mut stream,
+        );
+        // in the order they first ran, each member adding to the same phases
+        let phases: Vec<&str> = profile.phases.iter().map(|(p, _)| *p).collect();
+        assert_eq!(
+            phases,
+            [
+                "read file",
+                "cache",
+                "load sections",
+                "parse units",
+                "resolve files",
+                "walk DIEs",
+                "sort"
+            ]
+        );
+        // no clocks are read without --profile
+        let mut profile = Profile::new(false);
+        assert!(profile.start().is_none());
+        extract_tags(
+            &options.path,
+            &options,
+            &mut profile,
+            &mut stats,
+            &mut stream,
+        );
+        assert!(profile.phases.is_empty());
+    }
+
+    #[test]
+    fn include_only_files() {
+        let dir = temp_dir("include-only-files");
+        let manifest = dir.join("manifest");
+        // cleaned up the same way as the tags' names, "./dwo_main.c"
+        fs::write(
+            &manifest,
+            "./sub/../reloc.c\n\n  .\\arrays.c\ndwo_main.c\nmissing.c\n",
+        )
+        .unwrap();
+        let manifest = manifest.to_str().unwrap();
+        assert_eq!(
+            read_file_manifest(manifest).unwrap(),
+            ["reloc.c", "arrays.c", "dwo_main.c", "missing.c"]
+        );
+        let cache_dir = dir.join("cache");
+        let args = [
+            "--include-only-files",
+            manifest,
+            "--cache-dir",
+            cache_dir.to_str().unwrap(),
+        ];
+        let run = |input: &str| {
+            let fixtures = fixture("");
+            let args = [&args[..], &["--dwo-dir", &fixtures]].concat();
+            let options = fixture_options(&args, input);
+            let mut stats = Stats::default();
+            let list = read_fixture(&options, &mut stats);
+            let files: BTreeSet<String> = list.iter().map(|f| f.file_name.clone()).collect();
+            (files, stats.warnings.len(), stats.cache_hits)
+        };
+        let both = BTreeSet::from(["arrays.c".to_string(), "reloc.c".to_string()]);
+        // dwo_main.c and missing.c matched nothing
+        assert_eq!(run("objects.tar"), (both.clone(), 2, 0));
+        // tags from the cache match their entries the same way
+        assert_eq!(run("objects.tar"), (both, 2, 2));
+        let (files, warnings, _) = run("dwo");
+        assert_eq!(files, BTreeSet::from(["./dwo_main.c".to_string()]));
+        assert_eq!(warnings, 3);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn emit_provenance() {
+        let options = fixture_options(&["--emit-provenance"], "provenance");
+        let mut stream = None::<TagStream<std::io::Sink>>;
+        let (_, provenance) = extract_tags(
+            &options.path,
+            &options,
+            &mut Profile::new(false),
+            &mut Stats::default(),
+            &mut stream,
+        );
+        let header = pseudo_tags(&options, &provenance);
+        // the build-id readelf -n shows for it
+        assert_eq!(
+            header[2..],
+            [
+                "!_TAG_BUILD_ID\t022810c9c973cb83de3c57d3db9894f491150623\t/build-id of the input file/".to_string(),
+                "!_TAG_COMP_DIR\t.\t/compilation directory/".to_string(),
+                format!(
+                    "!_TAG_INPUT_FILE\t{}\t/file the tags were generated from/",
+                    fixture("provenance")
+                ),
+            ]
+        );
+        // a file without a build-id leaves that one out
+        let options = fixture_options(&["--emit-provenance"], "aliases");
+        let (_, provenance) = extract_tags(
+            &options.path,
+            &options,
+            &mut Profile::new(false),
+            &mut Stats::default(),
+            &mut stream,
+        );
+        let header = pseudo_tags(&options, &provenance);
+        assert_eq!(header.len(), 4);
+        assert!(header[2].starts_with("!_TAG_COMP_DIR\t"));
+    }
+
+    #[test]
+    fn tabs_to_spaces() {
+        assert_eq!(parse_tabs_to_spaces("escape"), Some(TabReplace::Escape));
+        assert_eq!(parse_tabs_to_spaces("4"), Some(TabReplace::Spaces(4)));
+        assert_eq!(parse_tabs_to_spaces("0"), Some(TabReplace::Spaces(0)));
+        assert_eq!(parse_tabs_to_spaces("tab"), None);
+        assert_eq!(parse_tabs_to_spaces("-1"), None);
+        // line 5 of code.cpp is "\treturn x * 3;"
+        let tag = function("inlined", &fixture("code.cpp"), 5);
+        let line = |args: &[&str]| {
+            let options = fixture_options(&[&["--excmd", "pattern"], args].concat(), "code.o");
+            format_tags(std::slice::from_ref(&tag), &options)[0].clone()
+        };
+        let file = fixture("code.cpp");
+        assert_eq!(
+            line(&[]),
+            format!("inlined\t{}\t/^\treturn x * 3;$/;\"\tf", file)
+        );
+        assert_eq!(
+            line(&["--tabs-to-spaces", "escape"]),
+            format!("inlined\t{}\t/^\\treturn x * 3;$/;\"\tf", file)
+        );
+        assert_eq!(
+            line(&["--tabs-to-spaces", "2"]),
+            format!("inlined\t{}\t/^  return x * 3;$/;\"\tf", file)
+        );
+    }
+}
@@ -0,0 +1,57 @@
+# GNAT's flattened names for Pkg.Child.Do_It and the main subprogram Main
+	.file	"pkg-child.adb"
+	.text
+	.globl	pkg__child__do_it
+	.type	pkg__child__do_it, @function
+pkg__child__do_it:
+	.file 1 "pkg-child.adb"
+	.loc 1 3 1
+	ret
+.Ldo_it_end:
+	.size	pkg__child__do_it, .-pkg__child__do_it
+
+	.section	.debug_abbrev,"",@progbits
+.Labbrev:
+	.uleb128 1		# compile_unit, with children
+	.uleb128 0x11
+	.byte	1
+	.uleb128 0x03, 0x08	# name, string
+	.uleb128 0x1b, 0x08	# comp_dir, string
+	.uleb128 0x13, 0x0b	# language, data1
+	.uleb128 0x10, 0x17	# stmt_list, sec_offset
+	.byte	0, 0
+	.uleb128 2		# subprogram
+	.uleb128 0x2e
+	.byte	0
+	.uleb128 0x03, 0x08	# name, string
+	.uleb128 0x3a, 0x0b	# decl_file, data1
+	.uleb128 0x3b, 0x0b	# decl_line, data1
+	.uleb128 0x3f, 0x19	# external, flag_present
+	.byte	0, 0
+	.byte	0
+
+	.section	.debug_info,"",@progbits
+.Linfo:
+	.long	.Linfo_end - .Linfo_start
+.Linfo_start:
+	.value	4
+	.long	.Labbrev
+	.byte	8
+	.uleb128 1
+	.string	"pkg-child.adb"
+	.string	"."
+	.byte	0x0d		# Ada95
+	.long	.Lline
+	.uleb128 2
+	.string	"pkg__child__do_it"
+	.byte	1
+	.byte	3
+	.uleb128 2
+	.string	"_ada_main"
+	.byte	1
+	.byte	10
+	.byte	0
+.Linfo_end:
+
+	.section	.debug_line,"",@progbits
+.Lline:
@@ -0,0 +1,55 @@
+# A nameless subprogram at an address with three symbols. The linker writes
+# them in its own order, z_work before a_work before work.
+	.file	"aliases.c"
+	.text
+	.globl	a_work, work, z_work
+	.type	a_work, @function
+	.type	work, @function
+	.type	z_work, @function
+	.set	a_work, code
+	.set	work, code
+	.set	z_work, code
+code:
+	.file 1 "aliases.c"
+	.loc 1 2 1
+	ret
+.Lcode_end:
+
+	.section	.debug_abbrev,"",@progbits
+.Labbrev:
+	.uleb128 1		# compile_unit, with children
+	.uleb128 0x11
+	.byte	1
+	.uleb128 0x03, 0x08	# name, string
+	.uleb128 0x1b, 0x08	# comp_dir, string
+	.uleb128 0x13, 0x0b	# language, data1
+	.uleb128 0x10, 0x17	# stmt_list, sec_offset
+	.byte	0, 0
+	.uleb128 2		# subprogram
+	.uleb128 0x2e
+	.byte	0
+	.uleb128 0x11, 0x01	# low_pc, addr
+	.uleb128 0x12, 0x07	# high_pc, data8
+	.byte	0, 0
+	.byte	0
+
+	.section	.debug_info,"",@progbits
+.Linfo:
+	.long	.Linfo_end - .Linfo_start
+.Linfo_start:
+	.value	4
+	.long	.Labbrev
+	.byte	8
+	.uleb128 1
+	.string	"aliases.c"
+	.string	"."
+	.byte	0x0c		# C99
+	.long	.Lline
+	.uleb128 2
+	.quad	code
+	.quad	.Lcode_end - code
+	.byte	0
+.Linfo_end:
+
+	.section	.debug_line,"",@progbits
+.Lline:
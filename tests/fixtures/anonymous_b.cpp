@@ -0,0 +1,5 @@
+namespace {
+int helper() { return 1; }
+}
+
+int use_b() { return helper(); }
@@ -0,0 +1,8 @@
+int grid[3][4];
+char name[16];
+const char *labels[2] = { "a", "b" };
+
+int size(void)
+{
+	return sizeof grid + sizeof name;
+}
@@ -0,0 +1,29 @@
+# break_section.py <in> <out> <section> <offset|size|reloc> <value>
+# Write a copy of a little endian ELF64 file with a broken section header:
+# the offset or size of the section set to value, or with reloc the offset
+# of the first relocation in the section.
+import struct
+import sys
+
+src, dst, name, field, value = sys.argv[1:]
+value = int(value, 0)
+data = bytearray(open(src, "rb").read())
+shoff = struct.unpack_from("<Q", data, 0x28)[0]
+shentsize, shnum, shstrndx = struct.unpack_from("<HHH", data, 0x3A)
+
+
+def header(i):
+    return shoff + i * shentsize
+
+
+names = struct.unpack_from("<Q", data, header(shstrndx) + 0x18)[0]
+for i in range(shnum):
+    start = names + struct.unpack_from("<I", data, header(i))[0]
+    if data[start : data.index(0, start)].decode() != name:
+        continue
+    if field == "reloc":
+        offset = struct.unpack_from("<Q", data, header(i) + 0x18)[0]
+        struct.pack_into("<Q", data, offset, value)
+    else:
+        struct.pack_into("<Q", data, header(i) + {"offset": 0x18, "size": 0x20}[field], value)
+open(dst, "wb").write(data)
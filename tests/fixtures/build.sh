@@ -0,0 +1,90 @@
+#!/bin/sh
+# Rebuilds the fixtures read by the tests in src/lib.rs. Run it from this
+# directory, the outputs are checked in so it's only needed to change one.
+set -e
+
+# debug info written by hand, for what compilers don't produce
+gcc -c origin.s -o origin.o
+gcc -c ada.s -o ada.o
+gcc -c deep.s -o deep.o
+gcc -c visibility.s -o visibility.o
+gcc -c damaged.s -o damaged.o
+gcc -c implicit.s -o implicit.o
+gcc -nostdlib -Wl,-e,split_up -Wl,--build-id=none ranges.s -o ranges
+gcc -nostdlib -Wl,-e,a_work -Wl,--build-id=none aliases.s -o aliases
+
+# compiled, with the build directory left out of the debug info
+CXX="g++ -g -fdebug-prefix-map=$PWD=."
+$CXX -c imported.cpp -o imported.o
+$CXX -c templates.cpp -o templates.o
+$CXX -c qualifiers.cpp -o qualifiers.o
+$CXX -c anonymous_a.cpp -o anonymous_a.o
+$CXX -c anonymous_b.cpp -o anonymous_b.o
+ld -r anonymous_a.o anonymous_b.o -o anonymous.o
+rm anonymous_a.o anonymous_b.o
+$CXX -c -gdwarf-4 -fdebug-types-section types.cpp -o types.o
+$CXX -c code.cpp -o code.o
+CC="gcc -g -fdebug-prefix-map=$PWD=."
+$CC -c -ffunction-sections reloc.c -o reloc.o
+# linked with a build-id, for --emit-provenance
+$CC -nostdlib -Wl,-e,first -Wl,--build-id=sha1 reloc.c -o provenance
+# linked at a fixed base, for --load-bias
+$CC -nostdlib -no-pie -Wl,-e,first -Wl,--build-id=none reloc.c -o fixed-base
+for f in dwo_main dwo_helper; do
+	$CC -gsplit-dwarf -c $f.c -o $f.o
+done
+gcc -nostdlib -Wl,-e,main -Wl,--build-id=none dwo_main.o dwo_helper.o -o dwo
+rm dwo_main.o dwo_helper.o
+$CC -c arrays.c -o arrays.o
+# a function of a header in two units
+for f in repeated_a repeated_b; do
+	$CC -c $f.c -o $f.o
+done
+ld -r repeated_a.o repeated_b.o -o repeated.o
+rm repeated_a.o repeated_b.o
+$CC -std=gnu17 -c prototyped.c -o prototyped.o
+# -ffreestanding leaves out the macros of stdc-predef.h
+$CC -g3 -ffreestanding -c macros.c -o macros.o
+$CC -g3 -gdwarf-4 -gstrict-dwarf -ffreestanding -c macros.c -o macinfo.o
+
+# a supplementary file holding the strings, named by .debug_sup
+$CC -gdwarf-5 -nostdlib -Wl,-e,main -Wl,--build-id=none sup.c -o sup-full
+objcopy --dump-section .debug_abbrev=sup-abbrev sup-full
+python3 strp_sup.py sup-abbrev sup-abbrev
+# .debug_sup: version 5, is_supplementary, the file name and no checksum
+printf '\005\000\001\000\000' > sup-section
+objcopy --only-section=.debug_str --add-section .debug_sup=sup-section sup-full sup.debug
+printf '\005\000\000sup.debug\000\000' > sup-section
+objcopy --update-section .debug_abbrev=sup-abbrev --remove-section .debug_str \
+	--add-section .debug_sup=sup-section sup-full sup-main
+rm sup-full sup-abbrev sup-section
+
+# LLVM IR, for a line table with MD5 checksums and for a big endian target
+llc -filetype=obj md5.ll -o md5.o
+llc -filetype=obj -mtriple=x86_64-unknown-linux-gnu endian.ll -o endian-le.o
+llc -filetype=obj -mtriple=powerpc64-unknown-linux-gnu endian.ll -o endian-be.o
+
+# only one string section compressed, each way
+python3 compress_section.py md5.o .debug_str zlib md5-str-zlib.o
+python3 compress_section.py md5.o .debug_str_offsets zstd md5-str-offsets-zstd.o
+
+# broken section headers and relocations
+python3 break_section.py reloc.o bad-size.o .debug_info size 0x7fffffff
+python3 break_section.py reloc.o bad-offset.o .debug_str offset 0xffffffffffff0000
+python3 break_section.py reloc.o bad-relocations.o .rela.debug_info size 0x100000
+python3 break_section.py reloc.o bad-relocation.o .rela.debug_info reloc 0x10000
+
+# an archive of two objects
+tar --mtime=@0 --owner=0 --group=0 --numeric-owner --format=ustar -cf objects.tar reloc.o arrays.o
+
+# C, C++ and a unit without a language in one object, for --kinds per language
+gcc -c nolang.s -o nolang.o
+ld -r arrays.o imported.o nolang.o -o mixed.o
+rm nolang.o
+
+# Rust
+RUSTC="rustc -g --crate-type=lib --emit=obj -C opt-level=0 -C codegen-units=1 --remap-path-prefix=$PWD=."
+$RUSTC traits.rs -o traits.o
+# v0 mangling, for the shim's "{shim:vtable#0}"
+$RUSTC -C symbol-mangling-version=v0 closures.rs -o closures.o
+$RUSTC unicode.rs -o unicode.o
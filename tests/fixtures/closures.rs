@@ -0,0 +1,15 @@
+pub fn total(values: &[i32]) -> i32 {
+    values.iter().map(|v| v * 2).sum()
+}
+
+pub fn nested(x: i32) -> i32 {
+    let outer = |y: i32| {
+        let inner = |z: i32| z + x;
+        inner(y)
+    };
+    outer(1)
+}
+
+pub fn boxed(x: i32) -> Box<dyn FnOnce() -> i32> {
+    Box::new(move || x + 1)
+}
@@ -0,0 +1,22 @@
+extern int declared(int x);
+
+static inline __attribute__((always_inline)) int inlined(int x)
+{
+	return x * 3;
+}
+
+struct Counter {
+	int count;
+	int next();
+	int unused();
+};
+
+int Counter::next()
+{
+	return ++count;
+}
+
+int compiled(int x)
+{
+	return inlined(x) + declared(x);
+}
@@ -0,0 +1,47 @@
+# compress_section.py <elf> <section> <zlib|zstd> <out>
+# Copy a little endian 64-bit elf file to out with just the named section
+# compressed the way -gz does it, an Elf64_Chdr and the compressed contents,
+# which are appended to the file. Unlike objcopy this also compresses
+# sections too small to gain from it.
+import struct
+import subprocess
+import sys
+import zlib
+
+SHF_COMPRESSED = 0x800
+COMPRESS_TYPES = {"zlib": 1, "zstd": 2}
+
+
+def section_header(data, wanted):
+    shoff, = struct.unpack_from("<Q", data, 0x28)
+    shentsize, shnum, shstrndx = struct.unpack_from("<HHH", data, 0x3A)
+    headers = [shoff + i * shentsize for i in range(shnum)]
+    names_offset, = struct.unpack_from("<Q", data, headers[shstrndx] + 0x18)
+    for header in headers:
+        name, = struct.unpack_from("<I", data, header)
+        start = names_offset + name
+        if data[start:data.index(b"\0", start)] == wanted.encode():
+            return header
+    sys.exit("no section " + wanted)
+
+
+elf, name, kind, out = sys.argv[1:]
+data = bytearray(open(elf, "rb").read())
+header = section_header(data, name)
+flags, = struct.unpack_from("<Q", data, header + 0x08)
+offset, size = struct.unpack_from("<QQ", data, header + 0x18)
+addralign, = struct.unpack_from("<Q", data, header + 0x30)
+contents = bytes(data[offset:offset + size])
+if kind == "zlib":
+    compressed = zlib.compress(contents)
+else:
+    compressed = subprocess.run(["zstd", "-c", "-q"], input=contents,
+                                stdout=subprocess.PIPE, check=True).stdout
+chdr = struct.pack("<IIQQ", COMPRESS_TYPES[kind], 0, size, addralign)
+while len(data) % 8:
+    data.append(0)
+struct.pack_into("<Q", data, header + 0x08, flags | SHF_COMPRESSED)
+struct.pack_into("<QQ", data, header + 0x18, len(data), len(chdr) + len(compressed))
+struct.pack_into("<Q", data, header + 0x30, 8)
+data += chdr + compressed
+open(out, "wb").write(data)
@@ -0,0 +1,89 @@
+# A .debug_info with a name that isn't UTF-8, a DIE with an attribute form
+# that doesn't exist and a last unit that is cut off part way
+	.file	"damaged.c"
+	.text
+	.file 1 "damaged.c"
+	.loc 1 1 1
+	ret
+
+	.section	.debug_abbrev,"",@progbits
+.Labbrev:
+	.uleb128 1		# compile_unit, with children
+	.uleb128 0x11
+	.byte	1
+	.uleb128 0x03, 0x08	# name, string
+	.uleb128 0x1b, 0x08	# comp_dir, string
+	.uleb128 0x13, 0x0b	# language, data1
+	.uleb128 0x10, 0x17	# stmt_list, sec_offset
+	.byte	0, 0
+	.uleb128 2		# subprogram
+	.uleb128 0x2e
+	.byte	0
+	.uleb128 0x03, 0x08	# name, string
+	.uleb128 0x3a, 0x0b	# decl_file, data1
+	.uleb128 0x3b, 0x0b	# decl_line, data1
+	.byte	0, 0
+	.uleb128 3		# subprogram, damaged after its name
+	.uleb128 0x2e
+	.byte	0
+	.uleb128 0x03, 0x08	# name, string
+	.uleb128 0x3a, 0x7f	# decl_file, a form that doesn't exist
+	.uleb128 0x3b, 0x0b	# decl_line, data1
+	.byte	0, 0
+	.byte	0
+
+	.section	.debug_info,"",@progbits
+	.long	.Lunit1_end - .Lunit1_start
+.Lunit1_start:
+	.value	4
+	.long	.Labbrev
+	.byte	8
+	.uleb128 1
+	.string	"damaged.c"
+	.string	"."
+	.byte	0x0c		# C99
+	.long	.Lline
+	.uleb128 2
+	.string	"caf\351"	# latin-1
+	.byte	1
+	.byte	2
+	.uleb128 2
+	.string	"intact"
+	.byte	1
+	.byte	3
+	.byte	0
+.Lunit1_end:
+	.long	.Lunit2_end - .Lunit2_start
+.Lunit2_start:
+	.value	4
+	.long	.Labbrev
+	.byte	8
+	.uleb128 1
+	.string	"damaged.c"
+	.string	"."
+	.byte	0x0c		# C99
+	.long	.Lline
+	.uleb128 2
+	.string	"before_damage"
+	.byte	1
+	.byte	4
+	.uleb128 3
+	.string	"damaged"
+	.byte	1
+	.byte	5
+	.uleb128 2
+	.string	"after_damage"
+	.byte	1
+	.byte	6
+	.byte	0
+.Lunit2_end:
+	# says it is much longer than what is left of the section
+	.long	0x1000
+	.value	4
+	.long	.Labbrev
+	.byte	8
+	.uleb128 1
+	.string	"cut.c"
+
+	.section	.debug_line,"",@progbits
+.Lline:
@@ -0,0 +1,89 @@
+# A subprogram nested under 10000 lexical blocks, and two subprograms whose
+# abstract origins refer to each other
+	.file	"deep.c"
+	.text
+	.globl	cycle
+	.type	cycle, @function
+cycle:
+	.file 1 "deep.c"
+	.loc 1 3 1
+	ret
+.Lcycle_end:
+	.size	cycle, .-cycle
+
+	.section	.debug_abbrev,"",@progbits
+.Labbrev:
+	.uleb128 1		# compile_unit, with children
+	.uleb128 0x11
+	.byte	1
+	.uleb128 0x03, 0x08	# name, string
+	.uleb128 0x1b, 0x08	# comp_dir, string
+	.uleb128 0x13, 0x0b	# language, data1
+	.uleb128 0x10, 0x17	# stmt_list, sec_offset
+	.byte	0, 0
+	.uleb128 2		# subprogram
+	.uleb128 0x2e
+	.byte	0
+	.uleb128 0x03, 0x08	# name, string
+	.uleb128 0x3a, 0x0b	# decl_file, data1
+	.uleb128 0x3b, 0x0b	# decl_line, data1
+	.byte	0, 0
+	.uleb128 3		# lexical_block, with children
+	.uleb128 0x0b
+	.byte	1
+	.byte	0, 0
+	.uleb128 4		# subprogram, only an abstract origin
+	.uleb128 0x2e
+	.byte	0
+	.uleb128 0x31, 0x13	# abstract_origin, ref4
+	.byte	0, 0
+	.uleb128 5		# subprogram, with an abstract origin and code
+	.uleb128 0x2e
+	.byte	0
+	.uleb128 0x03, 0x08	# name, string
+	.uleb128 0x31, 0x13	# abstract_origin, ref4
+	.uleb128 0x3a, 0x0b	# decl_file, data1
+	.uleb128 0x3b, 0x0b	# decl_line, data1
+	.uleb128 0x11, 0x01	# low_pc, addr
+	.uleb128 0x12, 0x07	# high_pc, data8
+	.byte	0, 0
+	.byte	0
+
+	.section	.debug_info,"",@progbits
+.Linfo:
+	.long	.Linfo_end - .Linfo_start
+.Linfo_start:
+	.value	4
+	.long	.Labbrev
+	.byte	8
+	.uleb128 1
+	.string	"deep.c"
+	.string	"."
+	.byte	0x0c		# C99
+	.long	.Lline
+	.rept	10000
+	.uleb128 3
+	.endr
+	.uleb128 2
+	.string	"nested"
+	.byte	1
+	.byte	9
+	.rept	10000
+	.byte	0
+	.endr
+.Lfirst:
+	.uleb128 5
+	.string	"cycle"
+	.long	.Lsecond - .Linfo
+	.byte	1
+	.byte	3
+	.quad	cycle
+	.quad	.Lcycle_end - cycle
+.Lsecond:
+	.uleb128 4
+	.long	.Lfirst - .Linfo
+	.byte	0
+.Linfo_end:
+
+	.section	.debug_line,"",@progbits
+.Lline:
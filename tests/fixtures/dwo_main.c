@@ -0,0 +1,6 @@
+int helper(int x);
+
+int main(void)
+{
+	return helper(1);
+}
@@ -0,0 +1,26 @@
+; Built for both a little and a big endian target, which have to give the
+; same tags
+define i32 @first() !dbg !10 {
+  ret i32 0, !dbg !14
+}
+
+define i32 @second(i32 %x) !dbg !12 {
+  ret i32 %x, !dbg !15
+}
+
+!llvm.dbg.cu = !{!0}
+!llvm.module.flags = !{!3, !4}
+
+!0 = distinct !DICompileUnit(language: DW_LANG_C99, file: !1, producer: "hand written", isOptimized: false, runtimeVersion: 0, emissionKind: FullDebug)
+!1 = !DIFile(filename: "endian.c", directory: ".")
+!3 = !{i32 7, !"Dwarf Version", i32 4}
+!4 = !{i32 2, !"Debug Info Version", i32 3}
+!5 = !DISubroutineType(types: !6)
+!6 = !{!7}
+!7 = !DIBasicType(name: "int", size: 32, encoding: DW_ATE_signed)
+!8 = !DISubroutineType(types: !9)
+!9 = !{!7, !7}
+!10 = distinct !DISubprogram(name: "first", scope: !1, file: !1, line: 1, type: !5, spFlags: DISPFlagDefinition, unit: !0)
+!12 = distinct !DISubprogram(name: "second", scope: !1, file: !1, line: 6, type: !8, spFlags: DISPFlagDefinition, unit: !0)
+!14 = !DILocation(line: 3, column: 3, scope: !10)
+!15 = !DILocation(line: 8, column: 3, scope: !12)
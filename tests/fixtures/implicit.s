@@ -0,0 +1,62 @@
+# DWARF 5 subprograms whose decl_file, and for one abbreviation decl_line,
+# are DW_FORM_implicit_const values held by the abbreviation itself
+	.file	"implicit.c"
+	.text
+	.file 1 "implicit.c"
+	.loc 1 1 1
+	ret
+
+	.section	.debug_abbrev,"",@progbits
+.Labbrev:
+	.uleb128 1		# compile_unit, with children
+	.uleb128 0x11
+	.byte	1
+	.uleb128 0x03, 0x08	# name, string
+	.uleb128 0x1b, 0x08	# comp_dir, string
+	.uleb128 0x13, 0x0b	# language, data1
+	.uleb128 0x10, 0x17	# stmt_list, sec_offset
+	.byte	0, 0
+	.uleb128 2		# subprogram
+	.uleb128 0x2e
+	.byte	0
+	.uleb128 0x03, 0x08	# name, string
+	.uleb128 0x3a, 0x21	# decl_file, implicit_const 1
+	.sleb128 1
+	.uleb128 0x3b, 0x0b	# decl_line, data1
+	.byte	0, 0
+	.uleb128 3		# subprogram
+	.uleb128 0x2e
+	.byte	0
+	.uleb128 0x03, 0x08	# name, string
+	.uleb128 0x3a, 0x21	# decl_file, implicit_const 1
+	.sleb128 1
+	.uleb128 0x3b, 0x21	# decl_line, implicit_const 300
+	.sleb128 300
+	.byte	0, 0
+	.byte	0
+
+	.section	.debug_info,"",@progbits
+	.long	.Linfo_end - .Linfo_start
+.Linfo_start:
+	.value	5
+	.byte	1		# DW_UT_compile
+	.byte	8
+	.long	.Labbrev
+	.uleb128 1
+	.string	"implicit.c"
+	.string	"."
+	.byte	0x1d		# C11
+	.long	.Lline
+	.uleb128 2
+	.string	"one"
+	.byte	10
+	.uleb128 2
+	.string	"two"
+	.byte	20
+	.uleb128 3
+	.string	"three"
+	.byte	0
+.Linfo_end:
+
+	.section	.debug_line,"",@progbits
+.Lline:
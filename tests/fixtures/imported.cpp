@@ -0,0 +1,11 @@
+namespace lib {
+int helper(int x) { return x + 1; }
+namespace detail {
+int depth() { return 0; }
+}
+}
+namespace app {
+using lib::helper;
+namespace d = lib::detail;
+int run() { return helper(d::depth()); }
+}
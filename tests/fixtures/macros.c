@@ -0,0 +1,8 @@
+#define BUF_SIZE 64
+#define MAX(a, b) ((a) > (b) ? (a) : (b))
+
+int biggest(int x)
+{
+	return MAX(x, BUF_SIZE);
+}
+#undef BUF_SIZE
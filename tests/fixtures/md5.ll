@@ -0,0 +1,27 @@
+; Two functions in a DWARF 5 line table that records an MD5 checksum for
+; each file, one of them in a header
+target triple = "x86_64-unknown-linux-gnu"
+
+define i32 @main() !dbg !10 {
+  ret i32 0, !dbg !14
+}
+
+define i32 @helper() !dbg !12 {
+  ret i32 1, !dbg !15
+}
+
+!llvm.dbg.cu = !{!0}
+!llvm.module.flags = !{!3, !4}
+
+!0 = distinct !DICompileUnit(language: DW_LANG_C99, file: !1, producer: "hand written", isOptimized: false, runtimeVersion: 0, emissionKind: FullDebug)
+!1 = !DIFile(filename: "md5.c", directory: ".", checksumkind: CSK_MD5, checksum: "0123456789abcdef0123456789abcdef")
+!2 = !DIFile(filename: "md5.h", directory: ".", checksumkind: CSK_MD5, checksum: "fedcba9876543210fedcba9876543210")
+!3 = !{i32 7, !"Dwarf Version", i32 5}
+!4 = !{i32 2, !"Debug Info Version", i32 3}
+!5 = !DISubroutineType(types: !6)
+!6 = !{!7}
+!7 = !DIBasicType(name: "int", size: 32, encoding: DW_ATE_signed)
+!10 = distinct !DISubprogram(name: "main", scope: !1, file: !1, line: 4, type: !5, spFlags: DISPFlagDefinition, unit: !0)
+!12 = distinct !DISubprogram(name: "helper", scope: !2, file: !2, line: 2, type: !5, spFlags: DISPFlagDefinition, unit: !0)
+!14 = !DILocation(line: 5, column: 3, scope: !10)
+!15 = !DILocation(line: 3, column: 3, scope: !12)
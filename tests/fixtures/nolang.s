@@ -0,0 +1,46 @@
+# A unit without DW_AT_language, as some assemblers and older compilers
+# write it
+	.file	"nolang.c"
+	.text
+	.file 1 "nolang.c"
+	.loc 1 1 1
+	ret
+
+	.section	.debug_abbrev,"",@progbits
+.Labbrev:
+	.uleb128 1		# compile_unit, with children
+	.uleb128 0x11
+	.byte	1
+	.uleb128 0x03, 0x08	# name, string
+	.uleb128 0x1b, 0x08	# comp_dir, string
+	.uleb128 0x10, 0x17	# stmt_list, sec_offset
+	.byte	0, 0
+	.uleb128 2		# subprogram
+	.uleb128 0x2e
+	.byte	0
+	.uleb128 0x03, 0x08	# name, string
+	.uleb128 0x3a, 0x0b	# decl_file, data1
+	.uleb128 0x3b, 0x0b	# decl_line, data1
+	.byte	0, 0
+	.byte	0
+
+	.section	.debug_info,"",@progbits
+	.long	.Linfo_end - .Linfo_start
+.Linfo_start:
+	.value	5
+	.byte	1		# DW_UT_compile
+	.byte	8
+	.long	.Labbrev
+	.uleb128 1
+	.string	"nolang.c"
+	.string	"."
+	.long	.Lline
+	.uleb128 2
+	.string	"untyped"
+	.byte	1
+	.byte	1
+	.byte	0
+.Linfo_end:
+
+	.section	.debug_line,"",@progbits
+.Lline:
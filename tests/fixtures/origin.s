@@ -0,0 +1,72 @@
+# An out of line instance of an inline function that has decl coordinates of
+# its own, line 7, different from those of its abstract origin, line 2
+	.file	"origin.c"
+	.text
+	.globl	twice
+	.type	twice, @function
+twice:
+	.file 1 "origin.c"
+	.loc 1 7 1
+	leal	(%rdi,%rdi), %eax
+	ret
+.Ltwice_end:
+	.size	twice, .-twice
+
+	.section	.debug_abbrev,"",@progbits
+.Labbrev:
+	.uleb128 1		# compile_unit, with children
+	.uleb128 0x11
+	.byte	1
+	.uleb128 0x03, 0x08	# name, string
+	.uleb128 0x1b, 0x08	# comp_dir, string
+	.uleb128 0x13, 0x0b	# language, data1
+	.uleb128 0x10, 0x17	# stmt_list, sec_offset
+	.byte	0, 0
+	.uleb128 2		# subprogram, the abstract origin
+	.uleb128 0x2e
+	.byte	0
+	.uleb128 0x03, 0x08	# name, string
+	.uleb128 0x3a, 0x0b	# decl_file, data1
+	.uleb128 0x3b, 0x0b	# decl_line, data1
+	.uleb128 0x20, 0x0b	# inline, data1
+	.byte	0, 0
+	.uleb128 3		# subprogram, the instance
+	.uleb128 0x2e
+	.byte	0
+	.uleb128 0x31, 0x13	# abstract_origin, ref4
+	.uleb128 0x3a, 0x0b	# decl_file, data1
+	.uleb128 0x3b, 0x0b	# decl_line, data1
+	.uleb128 0x11, 0x01	# low_pc, addr
+	.uleb128 0x12, 0x07	# high_pc, data8
+	.byte	0, 0
+	.byte	0
+
+	.section	.debug_info,"",@progbits
+.Linfo:
+	.long	.Linfo_end - .Linfo_start
+.Linfo_start:
+	.value	4
+	.long	.Labbrev
+	.byte	8
+	.uleb128 1
+	.string	"origin.c"
+	.string	"."
+	.byte	0x0c		# C99
+	.long	.Lline
+.Lorigin:
+	.uleb128 2
+	.string	"twice"
+	.byte	1
+	.byte	2
+	.byte	3		# declared inline and inlined
+	.uleb128 3
+	.long	.Lorigin - .Linfo
+	.byte	1
+	.byte	7
+	.quad	twice
+	.quad	.Ltwice_end - twice
+	.byte	0
+.Linfo_end:
+
+	.section	.debug_line,"",@progbits
+.Lline:
@@ -0,0 +1,11 @@
+int old_style(a, b)
+	int a;
+	int b;
+{
+	return a + b;
+}
+
+int new_style(int a, int b)
+{
+	return a - b;
+}
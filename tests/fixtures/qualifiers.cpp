@@ -0,0 +1,20 @@
+struct X {
+	int f() const;
+	int f();
+	int g() &&;
+	int g() &;
+	void h(int a, const char *b, ...);
+	int k(int n) const volatile { return n; }
+};
+
+int X::f() const { return 1; }
+int X::f() { return 2; }
+int X::g() && { return 3; }
+int X::g() & { return 4; }
+void X::h(int a, const char *b, ...) {}
+
+int use(X &x)
+{
+	volatile X &v = x;
+	return v.k(1);
+}
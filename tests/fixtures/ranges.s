@@ -0,0 +1,60 @@
+# A subprogram with neither a name nor low_pc, only DW_AT_ranges, whose name
+# is in the symbol table
+	.file	"ranges.c"
+	.text
+	.globl	split_up
+	.type	split_up, @function
+split_up:
+	.file 1 "ranges.c"
+	.loc 1 4 1
+	xorl	%eax, %eax
+	ret
+.Lhot_end:
+	.size	split_up, .-split_up
+	.section	.text.unlikely,"ax",@progbits
+.Lcold:
+	ud2
+.Lcold_end:
+
+	.section	.debug_abbrev,"",@progbits
+.Labbrev:
+	.uleb128 1		# compile_unit, with children
+	.uleb128 0x11
+	.byte	1
+	.uleb128 0x03, 0x08	# name, string
+	.uleb128 0x1b, 0x08	# comp_dir, string
+	.uleb128 0x13, 0x0b	# language, data1
+	.uleb128 0x10, 0x17	# stmt_list, sec_offset
+	.byte	0, 0
+	.uleb128 2		# subprogram
+	.uleb128 0x2e
+	.byte	0
+	.uleb128 0x55, 0x17	# ranges, sec_offset
+	.byte	0, 0
+	.byte	0
+
+	.section	.debug_info,"",@progbits
+.Linfo:
+	.long	.Linfo_end - .Linfo_start
+.Linfo_start:
+	.value	4
+	.long	.Labbrev
+	.byte	8
+	.uleb128 1
+	.string	"ranges.c"
+	.string	"."
+	.byte	0x0c		# C99
+	.long	.Lline
+	.uleb128 2
+	.long	.Lranges
+	.byte	0
+.Linfo_end:
+
+	.section	.debug_ranges,"",@progbits
+.Lranges:
+	.quad	split_up, .Lhot_end
+	.quad	.Lcold, .Lcold_end
+	.quad	0, 0
+
+	.section	.debug_line,"",@progbits
+.Lline:
@@ -0,0 +1,9 @@
+static int counter;
+
+int first(void) { return counter; }
+
+int second(int x)
+{
+	counter += x;
+	return counter;
+}
@@ -0,0 +1,6 @@
+#include "repeated.h"
+
+int use_one(void)
+{
+	return twice(1);
+}
@@ -0,0 +1,6 @@
+#include "repeated.h"
+
+int use_two(void)
+{
+	return twice(2);
+}
@@ -0,0 +1,40 @@
+# strp_sup.py <debug_abbrev> <out>
+# Change every DW_FORM_strp in a .debug_abbrev to DW_FORM_strp_sup, so the
+# names are read from the .debug_str of the supplementary file instead.
+import sys
+
+DW_FORM_strp = 0x0E
+DW_FORM_strp_sup = 0x1D
+DW_FORM_implicit_const = 0x21
+
+
+def uleb(data, i):
+    value, shift = 0, 0
+    while True:
+        byte = data[i]
+        i += 1
+        value |= (byte & 0x7F) << shift
+        shift += 7
+        if not byte & 0x80:
+            return value, i
+
+
+data = bytearray(open(sys.argv[1], "rb").read())
+i = 0
+while i < len(data):
+    code, i = uleb(data, i)
+    if code == 0:
+        continue
+    _tag, i = uleb(data, i)
+    i += 1  # has children
+    while True:
+        attr, i = uleb(data, i)
+        form_at = i
+        form, i = uleb(data, i)
+        if (attr, form) == (0, 0):
+            break
+        if form == DW_FORM_strp:
+            data[form_at] = DW_FORM_strp_sup
+        if form == DW_FORM_implicit_const:
+            _value, i = uleb(data, i)
+open(sys.argv[2], "wb").write(data)
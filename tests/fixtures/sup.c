@@ -0,0 +1,9 @@
+int shared_helper(int x)
+{
+	return x + 1;
+}
+
+int main(void)
+{
+	return shared_helper(0);
+}
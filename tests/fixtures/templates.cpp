@@ -0,0 +1,13 @@
+namespace box {
+template <typename T, int N>
+struct Array {
+	T items[N];
+	T first() const { return items[0]; }
+};
+}
+
+int use()
+{
+	box::Array<char, 2> a{ { 'x', 'y' } };
+	return a.first();
+}
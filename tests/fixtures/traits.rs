@@ -0,0 +1,21 @@
+use std::fmt;
+
+pub struct Meters(pub f64);
+
+impl fmt::Display for Meters {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}m", self.0)
+    }
+}
+
+impl Meters {
+    pub fn double(&self) -> Meters {
+        Meters(self.0 * 2.0)
+    }
+}
+
+impl From<f64> for Meters {
+    fn from(value: f64) -> Meters {
+        Meters(value)
+    }
+}
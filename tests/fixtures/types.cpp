@@ -0,0 +1,17 @@
+namespace shapes {
+struct Circle {
+	double radius;
+	double area() const;
+	static Circle unit();
+};
+}
+
+double shapes::Circle::area() const
+{
+	return 3.14159 * radius * radius;
+}
+
+shapes::Circle shapes::Circle::unit()
+{
+	return Circle{ 1.0 };
+}
@@ -0,0 +1,25 @@
+#![allow(non_snake_case)]
+
+pub fn apple() -> i32 {
+    1
+}
+
+pub fn Apple2() -> i32 {
+    2
+}
+
+pub fn école() -> i32 {
+    3
+}
+
+pub fn ecrire() -> i32 {
+    4
+}
+
+pub fn Zebra() -> i32 {
+    5
+}
+
+pub fn _private() -> i32 {
+    6
+}
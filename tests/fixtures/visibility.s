@@ -0,0 +1,71 @@
+# Subprograms with each DW_AT_visibility, as a Modula or Pascal compiler
+# writes it, and one without
+	.file	"visibility.c"
+	.text
+	.file 1 "visibility.c"
+	.loc 1 1 1
+	ret
+
+	.section	.debug_abbrev,"",@progbits
+.Labbrev:
+	.uleb128 1		# compile_unit, with children
+	.uleb128 0x11
+	.byte	1
+	.uleb128 0x03, 0x08	# name, string
+	.uleb128 0x1b, 0x08	# comp_dir, string
+	.uleb128 0x13, 0x0b	# language, data1
+	.uleb128 0x10, 0x17	# stmt_list, sec_offset
+	.byte	0, 0
+	.uleb128 2		# subprogram
+	.uleb128 0x2e
+	.byte	0
+	.uleb128 0x03, 0x08	# name, string
+	.uleb128 0x3a, 0x0b	# decl_file, data1
+	.uleb128 0x3b, 0x0b	# decl_line, data1
+	.uleb128 0x17, 0x0b	# visibility, data1
+	.byte	0, 0
+	.uleb128 3		# subprogram, without a visibility
+	.uleb128 0x2e
+	.byte	0
+	.uleb128 0x03, 0x08	# name, string
+	.uleb128 0x3a, 0x0b	# decl_file, data1
+	.uleb128 0x3b, 0x0b	# decl_line, data1
+	.byte	0, 0
+	.byte	0
+
+	.section	.debug_info,"",@progbits
+.Linfo:
+	.long	.Linfo_end - .Linfo_start
+.Linfo_start:
+	.value	4
+	.long	.Labbrev
+	.byte	8
+	.uleb128 1
+	.string	"visibility.c"
+	.string	"."
+	.byte	0x0c		# C99
+	.long	.Lline
+	.uleb128 2
+	.string	"hidden"
+	.byte	1
+	.byte	1
+	.byte	1		# local
+	.uleb128 2
+	.string	"shown"
+	.byte	1
+	.byte	2
+	.byte	2		# exported
+	.uleb128 2
+	.string	"between"
+	.byte	1
+	.byte	3
+	.byte	3		# qualified
+	.uleb128 3
+	.string	"plain"
+	.byte	1
+	.byte	4
+	.byte	0
+.Linfo_end:
+
+	.section	.debug_line,"",@progbits
+.Lline: